@@ -0,0 +1,107 @@
+// Which bracket pairs the checker knows about and how each is scored.
+//
+// Grammar files have one pair per line: opener, closer, the score for finding that closer where
+// it doesn't belong, and the score for completing it, e.g. `( ) 3 1`. Blank lines and lines
+// starting with `#` are skipped.
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub struct BracketGrammar {
+    // Closer to its opener and corruption score.
+    closers: HashMap<char, (char, u64)>,
+    // Opener to its closer and completion score.
+    openers: HashMap<char, (char, u64)>,
+}
+
+impl BracketGrammar {
+    // The puzzle's four pairs.
+    pub fn standard() -> BracketGrammar {
+        let mut grammar = BracketGrammar {
+            closers: HashMap::new(),
+            openers: HashMap::new(),
+        };
+        for &(open, close, corruption, completion) in &[
+            ('(', ')', 3, 1),
+            ('[', ']', 57, 2),
+            ('{', '}', 1197, 3),
+            ('<', '>', 25137, 4),
+        ] {
+            grammar.add_pair(open, close, corruption, completion).ok();
+        }
+        grammar
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<BracketGrammar, &'static str> {
+        let text = fs::read_to_string(path).map_err(|_| "Can't read grammar")?;
+        BracketGrammar::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<BracketGrammar, &'static str> {
+        let mut grammar = BracketGrammar {
+            closers: HashMap::new(),
+            openers: HashMap::new(),
+        };
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 4 {
+                return Err("Grammar lines must be opener, closer and two scores");
+            }
+            let single = |field: &str| {
+                let mut chars = field.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(c),
+                    _ => Err("Brackets must be single characters"),
+                }
+            };
+            let score = |field: &str| field.parse().map_err(|_| "Scores must be whole numbers");
+            grammar.add_pair(
+                single(fields[0])?,
+                single(fields[1])?,
+                score(fields[2])?,
+                score(fields[3])?,
+            )?;
+        }
+        if grammar.openers.is_empty() {
+            return Err("Grammar has no bracket pairs");
+        }
+        Ok(grammar)
+    }
+
+    // Every bracket may only appear once across all pairs, or lines couldn't be read unambiguously.
+    fn add_pair(
+        &mut self,
+        open: char,
+        close: char,
+        corruption: u64,
+        completion: u64,
+    ) -> Result<(), &'static str> {
+        if open == close || [open, close].iter().any(|c| self.is_bracket(*c)) {
+            return Err("Bracket pairs overlap");
+        }
+        self.openers.insert(open, (close, completion));
+        self.closers.insert(close, (open, corruption));
+        Ok(())
+    }
+
+    fn is_bracket(&self, c: char) -> bool {
+        self.openers.contains_key(&c) || self.closers.contains_key(&c)
+    }
+
+    pub fn is_opener(&self, c: char) -> bool {
+        self.openers.contains_key(&c)
+    }
+
+    // The opener a closer matches and the score for finding it out of place, if it's a closer.
+    pub fn closer(&self, c: char) -> Option<(char, u64)> {
+        self.closers.get(&c).copied()
+    }
+
+    // The closer an opener needs and the score for supplying it, if it's an opener.
+    pub fn opener(&self, c: char) -> Option<(char, u64)> {
+        self.openers.get(&c).copied()
+    }
+}
@@ -1,8 +1,15 @@
-use std::collections::HashMap;
+mod grammar;
+mod repair;
+mod stream;
+mod syntax;
+
 use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, BufReader};
 use std::path::Path;
 
+use grammar::BracketGrammar;
+use syntax::Checker;
+
 #[derive(Debug, Clone)]
 struct MainError {
     _msg: &'static str,
@@ -29,38 +36,58 @@ impl From<std::num::ParseIntError> for MainError {
 }
 
 fn main() -> Result<(), MainError> {
-    // ideally this is const but Rust can't do this without a 3rd party crate yet.
-    let bracket_map = HashMap::from([
-        (')', ('(', 3)),
-        (']', ('[', 57)),
-        ('}', ('{', 1197)),
-        ('>', ('<', 25137)),
-    ]);
-    let autoscore_map = HashMap::from([('(', 1), ('[', 2), ('{', 3), ('<', 4)]);
-    let lines = read_lines("./input")?.flatten();
+    // --grammar <file> swaps the puzzle's brackets for another set, see grammars/.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let grammar = match args.iter().position(|arg| arg == "--grammar") {
+        Some(i) => BracketGrammar::load(args.get(i + 1).ok_or("Missing value")?)?,
+        None => BracketGrammar::standard(),
+    };
+    // --fix prints the input with every line repaired, instead of scoring it.
+    if args.iter().any(|arg| arg == "--fix") {
+        let lines = read_lines("./input")?.map_while(Result::ok);
+        for (i, line) in lines.enumerate() {
+            let (fixed, edit_count) = repair::repair(&grammar, &line);
+            if edit_count > 0 {
+                eprintln!("line {}: {} edit(s)", i + 1, edit_count);
+            }
+            println!("{}", fixed);
+        }
+        return Ok(());
+    }
+    // --diagnostics explains every line that isn't valid.
+    let diagnostics = args.iter().any(|arg| arg == "--diagnostics");
     let mut scores = Vec::new();
-    'outer: for line in lines {
-        let mut stack = Vec::new();
-        for c in line.chars() {
-            if bracket_map.contains_key(&c) {
-                if !stack
-                    .pop()
-                    .map_or(false, |top| top == bracket_map.get(&c).unwrap().0)
-                {
-                    continue 'outer;
-                }
-            } else {
-                stack.push(c);
+    if diagnostics {
+        // Diagnostics quote the line, so this reads whole lines.
+        let lines = read_lines("./input")?.map_while(Result::ok);
+        for (i, line) in lines.enumerate() {
+            let mut checker = Checker::new(&grammar);
+            line.chars().for_each(|c| checker.push(c));
+            scores.extend(checker.completion_score());
+            if let Some(text) = checker.finish().render(i + 1, &line) {
+                println!("{}", text);
             }
         }
-        let mut fill_score: u64 = 0;
-        while let Some(c) = stack.pop() {
-            fill_score = fill_score * 5 + autoscore_map.get(&c).unwrap_or(&0);
+    } else {
+        let mut checker = Checker::new(&grammar);
+        for c in stream::Chars::new(BufReader::new(File::open("./input")?)) {
+            let c = c?;
+            if c == '\n' {
+                scores.extend(checker.completion_score());
+                checker = Checker::new(&grammar);
+            } else {
+                checker.push(c);
+            }
         }
-        scores.push(fill_score);
+        scores.extend(checker.completion_score());
+    }
+    if scores.is_empty() {
+        return Err("No incomplete lines".into());
     }
-    scores.sort();
-    println!("{}", scores[scores.len() / 2]);
+    // Only the middle score is needed, so partition around it instead of sorting.
+    let middle = scores.len() / 2;
+    let (_, median, _) = scores.select_nth_unstable(middle);
+    println!("{}", median);
     Ok(())
 }
 // The output`FromIterator<&Vec<u64>>` is not implemented for `[&Vec<u64>]`
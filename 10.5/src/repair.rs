@@ -0,0 +1,104 @@
+// Rewrites lines so they parse: corrupt characters are substituted or deleted, then any
+// missing closers are appended.
+use crate::grammar::BracketGrammar;
+use crate::syntax::{Checker, SyntaxReport};
+
+// Deeper searches grow exponentially; past this, errors are fixed one at a time instead.
+const MAX_EDITS: usize = 3;
+
+fn check(grammar: &BracketGrammar, line: &[char]) -> SyntaxReport {
+    let mut checker = Checker::new(grammar);
+    line.iter().for_each(|&c| checker.push(c));
+    checker.finish()
+}
+
+// Lines one edit away that deal with the corrupt character at `column`: dropping it, swapping
+// it for the closer that was expected, or turning it into its own opener.
+fn edits(
+    grammar: &BracketGrammar,
+    line: &[char],
+    column: usize,
+    found: char,
+    expected: Option<char>,
+) -> Vec<Vec<char>> {
+    let i = column - 1;
+    let mut ret = Vec::new();
+    let mut deleted = line.to_vec();
+    deleted.remove(i);
+    ret.push(deleted);
+    let replacements = expected
+        .into_iter()
+        .chain(grammar.closer(found).map(|(opener, _)| opener));
+    for replacement in replacements {
+        let mut substituted = line.to_vec();
+        substituted[i] = replacement;
+        ret.push(substituted);
+    }
+    ret
+}
+
+/**
+ * Returns the repaired line and how many characters were substituted or deleted. Searches
+ * breadth first for the fewest edits, preferring the shortest completion among equals.
+ */
+pub fn repair(grammar: &BracketGrammar, line: &str) -> (String, usize) {
+    let mut frontier = vec![line.chars().collect::<Vec<char>>()];
+    for edit_count in 0..=MAX_EDITS {
+        let mut best: Option<(Vec<char>, String)> = None;
+        let mut next = Vec::new();
+        for candidate in frontier {
+            match check(grammar, &candidate) {
+                SyntaxReport::Corrupt {
+                    column,
+                    found,
+                    expected,
+                } => next.extend(edits(grammar, &candidate, column, found, expected)),
+                SyntaxReport::Valid => {
+                    best = Some((candidate, String::new()));
+                    break;
+                }
+                SyntaxReport::Incomplete { completion } => {
+                    if best.as_ref().is_none_or(|(_, shortest)| {
+                        completion.chars().count() < shortest.chars().count()
+                    }) {
+                        best = Some((candidate, completion));
+                    }
+                }
+            }
+        }
+        if let Some((fixed, completion)) = best {
+            return (
+                fixed.into_iter().chain(completion.chars()).collect(),
+                edit_count,
+            );
+        }
+        frontier = next;
+    }
+    repair_greedily(grammar, line)
+}
+
+// Fixes each error in turn with the expected closer, or by deleting it when nothing was open.
+fn repair_greedily(grammar: &BracketGrammar, line: &str) -> (String, usize) {
+    let mut line: Vec<char> = line.chars().collect();
+    let mut edit_count = 0;
+    loop {
+        match check(grammar, &line) {
+            SyntaxReport::Corrupt {
+                column,
+                expected: Some(expected),
+                ..
+            } => line[column - 1] = expected,
+            SyntaxReport::Corrupt { column, .. } => {
+                line.remove(column - 1);
+            }
+            SyntaxReport::Valid => return (line.into_iter().collect(), edit_count),
+            SyntaxReport::Incomplete { completion } => {
+                return (
+                    line.into_iter().chain(completion.chars()).collect(),
+                    edit_count,
+                )
+            }
+        }
+        edit_count += 1;
+    }
+}
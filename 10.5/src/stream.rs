@@ -0,0 +1,51 @@
+// Decodes UTF-8 from a byte stream one char at a time, so input of any length can be read
+// without holding it in memory.
+use std::io::{self, BufRead};
+
+pub struct Chars<R> {
+    bytes: io::Bytes<R>,
+    // A byte that cut a multi-byte sequence short, held back to start the next char.
+    pending: Option<u8>,
+}
+
+impl<R: BufRead> Chars<R> {
+    pub fn new(reader: R) -> Chars<R> {
+        Chars {
+            bytes: reader.bytes(),
+            pending: None,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Chars<R> {
+    // Malformed sequences come out as U+FFFD.
+    type Item = io::Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = match self.pending.take().map(Ok).or_else(|| self.bytes.next())? {
+            Ok(byte) => byte,
+            Err(err) => return Some(Err(err)),
+        };
+        let (len, mut code) = match first {
+            0x00..=0x7f => return Some(Ok(first as char)),
+            0xc0..=0xdf => (2, first as u32 & 0x1f),
+            0xe0..=0xef => (3, first as u32 & 0x0f),
+            0xf0..=0xf7 => (4, first as u32 & 0x07),
+            _ => return Some(Ok(char::REPLACEMENT_CHARACTER)),
+        };
+        for _ in 1..len {
+            match self.bytes.next() {
+                Some(Ok(byte)) if byte & 0xc0 == 0x80 => code = code << 6 | (byte as u32 & 0x3f),
+                Some(Ok(byte)) => {
+                    self.pending = Some(byte);
+                    return Some(Ok(char::REPLACEMENT_CHARACTER));
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                None => return Some(Ok(char::REPLACEMENT_CHARACTER)),
+            }
+        }
+        Some(Ok(
+            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+        ))
+    }
+}
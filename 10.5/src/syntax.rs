@@ -0,0 +1,144 @@
+// Checks a line against a grammar and explains what's wrong with it.
+use crate::grammar::BracketGrammar;
+
+pub enum SyntaxReport {
+    Valid,
+    // `column` is 1-based, in characters. `expected` is None when nothing was open.
+    Corrupt {
+        column: usize,
+        found: char,
+        expected: Option<char>,
+    },
+    // The closers that would finish the line, innermost first.
+    Incomplete {
+        completion: String,
+    },
+}
+
+/**
+ * Checks one line a character at a time, so a line never has to be held in memory. Memory
+ * grows with how deeply brackets are nested, not with the length of the line.
+ */
+pub struct Checker<'a> {
+    grammar: &'a BracketGrammar,
+    stack: Vec<char>,
+    column: usize,
+    corrupt: Option<(usize, char, Option<char>)>,
+}
+
+impl<'a> Checker<'a> {
+    pub fn new(grammar: &'a BracketGrammar) -> Checker<'a> {
+        Checker {
+            grammar,
+            stack: Vec::new(),
+            column: 0,
+            corrupt: None,
+        }
+    }
+
+    // Anything after the first corrupt character is ignored.
+    pub fn push(&mut self, c: char) {
+        if self.corrupt.is_some() {
+            return;
+        }
+        self.column += 1;
+        if let Some((opener, _)) = self.grammar.closer(c) {
+            let top = self.stack.pop();
+            if top != Some(opener) {
+                let expected = top
+                    .and_then(|top| self.grammar.opener(top))
+                    .map(|(closer, _)| closer);
+                self.corrupt = Some((self.column, c, expected));
+            }
+        } else if self.grammar.is_opener(c) {
+            self.stack.push(c);
+        }
+    }
+
+    // Score for completing the line, if it's incomplete. Saturates rather than overflowing on
+    // very deeply nested lines.
+    pub fn completion_score(&self) -> Option<u64> {
+        if self.corrupt.is_some() || self.stack.is_empty() {
+            return None;
+        }
+        Some(self.stack.iter().rev().fold(0u64, |score, &c| {
+            let points = self.grammar.opener(c).map_or(0, |(_, points)| points);
+            score.saturating_mul(5).saturating_add(points)
+        }))
+    }
+
+    pub fn finish(self) -> SyntaxReport {
+        if let Some((column, found, expected)) = self.corrupt {
+            return SyntaxReport::Corrupt {
+                column,
+                found,
+                expected,
+            };
+        }
+        if self.stack.is_empty() {
+            return SyntaxReport::Valid;
+        }
+        SyntaxReport::Incomplete {
+            completion: self
+                .stack
+                .iter()
+                .rev()
+                .filter_map(|&c| self.grammar.opener(c).map(|(closer, _)| closer))
+                .collect(),
+        }
+    }
+}
+
+impl SyntaxReport {
+    /**
+     * Compiler-style explanation with a caret under the problem, e.g.
+     *
+     * error: line 3, column 13: expected `]`, found `}`
+     *   3 | {([(<{}[<>[]}>{[]{[(<()>
+     *     |             ^ expected `]`
+     */
+    pub fn render(&self, line_number: usize, line: &str) -> Option<String> {
+        let (column, headline, note) = match self {
+            SyntaxReport::Valid => return None,
+            SyntaxReport::Corrupt {
+                column,
+                found,
+                expected: Some(expected),
+            } => (
+                *column,
+                format!(
+                    "column {}: expected `{}`, found `{}`",
+                    column, expected, found
+                ),
+                format!("expected `{}`", expected),
+            ),
+            SyntaxReport::Corrupt {
+                column,
+                found,
+                expected: None,
+            } => (
+                *column,
+                format!("column {}: found `{}` with nothing open", column, found),
+                "nothing to close".to_owned(),
+            ),
+            SyntaxReport::Incomplete { completion } => (
+                line.chars().count() + 1,
+                format!("incomplete, missing `{}`", completion),
+                format!("add `{}`", completion),
+            ),
+        };
+        let gutter = line_number.to_string().len();
+        Some(format!(
+            "error: line {}, {}\n{:>gutter$} | {}\n{:gutter$} | {:>column$} {}\n",
+            line_number,
+            headline,
+            line_number,
+            line,
+            "",
+            "^",
+            note,
+            gutter = gutter,
+            column = column
+        ))
+    }
+}
@@ -1,6 +1,15 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use octopus::{Board, Rules};
+
+mod render;
+
+use render::Recorder;
 
 #[derive(Debug, Clone)]
 struct MainError {
@@ -27,119 +36,158 @@ impl From<std::num::ParseIntError> for MainError {
     }
 }
 
-struct Board {
-    // Rows, then columns.
-    data: Vec<Vec<u32>>,
-    flashed: Vec<Vec<bool>>,
+// Flash counts for each step simulated so far, plus the cycle the states fell into if one was found.
+struct History {
+    // counts[i] is the number of flashes during step i + 1.
+    counts: Vec<u64>,
+    // The board after `start` steps is the same as after `start + period` steps.
+    cycle: Option<(usize, usize)>,
 }
 
-type Coord = (usize, usize);
-
-impl Board {
-    fn width(&self) -> usize {
-        self.data.get(0).map(|row| row.len()).unwrap_or(0)
-    }
-
-    fn height(&self) -> usize {
-        self.data.len()
-    }
-
-    fn get(&self, coord: Coord) -> u32 {
-        let (x, y) = coord;
-        self.data[y][x]
-    }
-
-    fn get_flashed(&self, coord: Coord) -> bool {
-        let (x, y) = coord;
-        self.flashed[y][x]
-    }
-
-    fn get_mut(&mut self, coord: Coord) -> &mut u32 {
-        let (x, y) = coord;
-        self.data.get_mut(y).unwrap().get_mut(x).unwrap()
-    }
-
-    fn set_flashed(&mut self, coord: Coord) {
-        let (x, y) = coord;
-        self.flashed[y][x] = true;
-    }
-
-    fn get_adjacent(&self, coord: Coord) -> Vec<Coord> {
-        let (x, y) = coord;
-        // To allow for negative without panicking
-        let x = x as i8;
-        let y = y as i8;
-        vec![
-            (x - 1, y),
-            (x + 1, y),
-            (x, y - 1),
-            (x, y + 1),
-            (x - 1, y - 1),
-            (x - 1, y + 1),
-            (x + 1, y - 1),
-            (x + 1, y + 1),
-        ]
-        .iter()
-        .filter(|(x, y)| 0 <= *x && *x < self.width() as i8 && 0 <= *y && *y < self.height() as i8)
-        .map(|(x, y)| (*x as usize, *y as usize))
-        .collect()
+impl History {
+    /**
+     * Steps the board until its states repeat, or until `stop` says the history is long enough.
+     * `watch` sees the board after every step. With `play_through`, stepping carries on past the
+     * cycle until `stop`, so whatever's watching gets every step it asked for.
+     */
+    fn record(
+        board: &mut Board,
+        stop: impl Fn(&History) -> bool,
+        mut watch: impl FnMut(&Board) -> io::Result<()>,
+        play_through: bool,
+    ) -> io::Result<History> {
+        let mut history = History {
+            counts: Vec::new(),
+            cycle: None,
+        };
+        let initial = board.clone();
+        let mut seen = HashMap::from([(board.state_hash(), 0)]);
+        while !stop(&history) {
+            history.counts.push(board.step() as u64);
+            watch(board)?;
+            if history.cycle.is_some() {
+                continue;
+            }
+            let steps = history.counts.len();
+            let start = *seen.entry(board.state_hash()).or_insert(steps);
+            if start == steps {
+                continue;
+            }
+            // Guard against hash collisions by replaying up to `start` and comparing for real.
+            let mut replay = initial.clone();
+            for _ in 0..start {
+                replay.step();
+            }
+            if replay.energies() == board.energies() {
+                history.cycle = Some((start, steps - start));
+                if !play_through {
+                    break;
+                }
+                continue;
+            }
+            seen.insert(board.state_hash(), steps);
+        }
+        Ok(history)
     }
 
-    fn energize(&mut self, coord: Coord) -> u32 {
-        if self.get_flashed(coord) {
-            return 0;
-        }
-        let mut flash_count = 0;
-        let val = self.get_mut(coord);
-        if *val != 9 {
-            *val += 1;
-        } else {
-            *val = 0; // Flash!
-            flash_count += 1;
-            self.set_flashed(coord);
-            flash_count += self
-                .get_adjacent(coord)
-                .iter()
-                .map(|c| self.energize(*c))
-                .sum::<u32>();
-        }
-        flash_count
+    // The first step where every octopus flashed, if it happens at all.
+    fn first_sync(&self, octopi: u64) -> Option<usize> {
+        self.counts
+            .iter()
+            .position(|&count| count == octopi)
+            .map(|i| i + 1)
     }
 
-    /**
-     * Steps the board by one cycle, returning the number of octopi who flashed.
-     */
-    fn step(&mut self) -> u32 {
-        // Reset
-        self.flashed = vec![vec![false; self.data[0].len()]; self.data.len()];
-        let mut flash_count = 0;
-        for y in 0..self.height() {
-            for x in 0..self.width() {
-                flash_count += self.energize((x, y));
-            }
+    // Total flashes over the first `steps` steps, or None if the history doesn't reach that far.
+    fn total_flashes(&self, steps: u64) -> Option<u128> {
+        let sum = |counts: &[u64]| counts.iter().map(|&c| c as u128).sum::<u128>();
+        if steps <= self.counts.len() as u64 {
+            return Some(sum(&self.counts[..steps as usize]));
         }
-        flash_count
+        let (start, period) = self.cycle?;
+        let cycle = &self.counts[start..start + period];
+        let after = steps - start as u64;
+        Some(
+            sum(&self.counts[..start])
+                + sum(cycle) * (after / period as u64) as u128
+                + sum(&cycle[..(after % period as u64) as usize]),
+        )
     }
 }
 
 fn main() -> Result<(), MainError> {
     let data: Vec<Vec<u32>> = read_lines("./input")?
-        .flatten()
+        .map_while(Result::ok)
         .map(|line| line.chars().filter_map(|c| c.to_digit(10)).collect())
         .collect();
-    let mut board = Board {
-        flashed: vec![vec![false; data[0].len()]; data.len()],
-        data,
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let steps: Option<u64> = match args.iter().position(|arg| arg == "--steps") {
+        Some(i) => Some(args.get(i + 1).ok_or("Missing value")?.parse()?),
+        None => None,
+    };
+    let mut board = Board::new(data, Rules::from_args(&args)?);
+    let octopi = (board.width() * board.height()) as u64;
+    // --animate redraws the grid in the terminal after each step, --delay milliseconds apart;
+    // --frames <file.gif|dir> saves every step as a GIF frame or a numbered PPM file.
+    let animate = args.iter().any(|arg| arg == "--animate");
+    let delay = match args.iter().position(|arg| arg == "--delay") {
+        Some(i) => args.get(i + 1).ok_or("Missing value")?.parse()?,
+        None => 100,
     };
-    let mut i = 0;
-    loop {
-        i += 1;
-        let count = board.step();
-        if count == (board.width() * board.height()) as u32 {
-            println!("{}", i);
-            break;
+    let mut recorder = match args.iter().position(|arg| arg == "--frames") {
+        Some(i) => {
+            let path = args.get(i + 1).ok_or("Missing value")?;
+            Some(Recorder::create(path, &board, delay).map_err(|err| {
+                eprintln!("Couldn't start recording frames: {}", err);
+                MainError::from("Couldn't record frames")
+            })?)
+        }
+        None => None,
+    };
+    let playback = animate || recorder.is_some();
+    let mut step = 0;
+    let mut watch = |board: &Board| -> io::Result<()> {
+        if animate {
+            print!("\x1b[H\x1b[2JStep {}\n{}", step, render::ansi(board));
+            thread::sleep(Duration::from_millis(delay));
+        }
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record(board)?;
+        }
+        step += 1;
+        Ok(())
+    };
+    watch(&board)?;
+    let history = History::record(
+        &mut board,
+        |history| match steps {
+            // Without a horizon, the first synchronised step is all we're after. Once the states
+            // repeat without one, there's nothing left to wait for.
+            None => history.counts.last() == Some(&octopi) || history.cycle.is_some(),
+            Some(steps) => history.counts.len() as u64 >= steps,
+        },
+        watch,
+        playback,
+    )
+    .map_err(|err| {
+        eprintln!("Couldn't record frames: {}", err);
+        MainError::from("Couldn't record frames")
+    })?;
+    if let Some((start, period)) = history.cycle {
+        eprintln!("States repeat from step {} with period {}", start, period);
+    }
+    if let Some(steps) = steps {
+        let total = history.total_flashes(steps).ok_or("No cycle found")?;
+        println!("{} flashes after {} steps", total, steps);
+    }
+    match history.first_sync(octopi) {
+        Some(step) => println!("{}", step),
+        // Every state from here on has been seen already, so it'll never happen.
+        None if history.cycle.is_some() => {
+            eprintln!("The octopi never all flash at once");
+            return Err("Never synchronises".into());
         }
-        //println!("{:?}", board.data);
+        None => println!("No synchronised step within {} steps", history.counts.len()),
     }
     Ok(())
 }
@@ -0,0 +1,164 @@
+// Draws the octopus grid, for watching flashes ripple across it step by step.
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use octopus::Board;
+
+// Energy is drawn in this many shades, followed by one colour for octopi that just flashed.
+const SHADES: usize = 10;
+const FLASH: u8 = SHADES as u8;
+
+// Energy from a dark blue up to a pale one, so a flash's yellow stands out against any of them.
+fn colour(level: u8) -> [u8; 3] {
+    if level == FLASH {
+        return [255, 230, 120];
+    }
+    let level = level as u32;
+    [
+        (10 + 8 * level) as u8,
+        (20 + 14 * level) as u8,
+        (45 + 20 * level) as u8,
+    ]
+}
+
+fn level(board: &Board, coord: (usize, usize)) -> u8 {
+    if board.get_flashed(coord) {
+        return FLASH;
+    }
+    let shade = board.get(coord) as usize * (SHADES - 1) / board.rules().threshold.max(1) as usize;
+    shade.min(SHADES - 1) as u8
+}
+
+// Energies on their shade, with this step's flashes in bold on yellow.
+pub fn ansi(board: &Board) -> String {
+    let mut out = String::new();
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            let level = level(board, (x, y));
+            let [r, g, b] = colour(level);
+            out.push_str(&format!(
+                "\x1b[{}48;2;{};{};{}m\x1b[38;2;{}m{}",
+                if level == FLASH { "1;" } else { "" },
+                r,
+                g,
+                b,
+                if level == FLASH {
+                    "0;0;0"
+                } else {
+                    "255;255;255"
+                },
+                board.get((x, y))
+            ));
+            out.push_str("\x1b[0m");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// Small grids get blown up so each octopus is visible; big ones stay at a pixel each.
+fn cell_size(board: &Board) -> usize {
+    (512 / board.width().max(board.height()).max(1)).clamp(1, 16)
+}
+
+// One level per pixel, row by row.
+fn levels(board: &Board, cell: usize) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(board.width() * board.height() * cell * cell);
+    for y in 0..board.height() * cell {
+        for x in 0..board.width() * cell {
+            pixels.push(level(board, (x / cell, y / cell)));
+        }
+    }
+    pixels
+}
+
+// Writes one frame per step, either into an animated GIF or as numbered PPM files in a directory.
+pub enum Recorder {
+    Ppm {
+        dir: PathBuf,
+        frame: usize,
+    },
+    #[cfg(feature = "gif")]
+    Gif {
+        encoder: gif::Encoder<BufWriter<File>>,
+        // Hundredths of a second between frames.
+        delay: u16,
+    },
+}
+
+impl Recorder {
+    // A path ending in .gif gets an animation; anything else is taken as a directory for PPM frames.
+    pub fn create<P: AsRef<Path>>(path: P, board: &Board, delay_ms: u64) -> io::Result<Recorder> {
+        let path = path.as_ref();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("gif") {
+            fs::create_dir_all(path)?;
+            return Ok(Recorder::Ppm {
+                dir: path.to_path_buf(),
+                frame: 0,
+            });
+        }
+        Recorder::create_gif(path, board, delay_ms)
+    }
+
+    #[cfg(feature = "gif")]
+    fn create_gif(path: &Path, board: &Board, delay_ms: u64) -> io::Result<Recorder> {
+        use std::convert::TryFrom;
+
+        let cell = cell_size(board);
+        let too_big = || io::Error::new(io::ErrorKind::InvalidInput, "grid is too big for a GIF");
+        let width = u16::try_from(board.width() * cell).map_err(|_| too_big())?;
+        let height = u16::try_from(board.height() * cell).map_err(|_| too_big())?;
+        let palette: Vec<u8> = (0..=FLASH).flat_map(colour).collect();
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder =
+            gif::Encoder::new(file, width, height, &palette).map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+        Ok(Recorder::Gif {
+            encoder,
+            delay: (delay_ms / 10).min(u16::MAX as u64) as u16,
+        })
+    }
+
+    #[cfg(not(feature = "gif"))]
+    fn create_gif(_: &Path, _: &Board, _: u64) -> io::Result<Recorder> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "GIF export needs the `gif` feature",
+        ))
+    }
+
+    pub fn record(&mut self, board: &Board) -> io::Result<()> {
+        let cell = cell_size(board);
+        match self {
+            Recorder::Ppm { dir, frame } => {
+                let path = dir.join(format!("step{:05}.ppm", frame));
+                *frame += 1;
+                let mut file = BufWriter::new(File::create(path)?);
+                write!(
+                    file,
+                    "P6\n{} {}\n255\n",
+                    board.width() * cell,
+                    board.height() * cell
+                )?;
+                for level in levels(board, cell) {
+                    file.write_all(&colour(level))?;
+                }
+                file.flush()
+            }
+            #[cfg(feature = "gif")]
+            Recorder::Gif { encoder, delay } => {
+                let frame = gif::Frame {
+                    width: (board.width() * cell) as u16,
+                    height: (board.height() * cell) as u16,
+                    delay: *delay,
+                    buffer: levels(board, cell).into(),
+                    ..gif::Frame::default()
+                };
+                encoder.write_frame(&frame).map_err(io::Error::other)
+            }
+        }
+    }
+}
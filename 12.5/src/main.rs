@@ -1,8 +1,9 @@
-use std::collections::HashMap;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
+use caves::{CaveGraph, NodeId};
+
 #[derive(Debug, Clone)]
 struct MainError {
     _msg: &'static str,
@@ -28,72 +29,54 @@ impl From<std::num::ParseIntError> for MainError {
     }
 }
 
-// Ideally we'd do this with a graph, but implementing a graph type in Rust is a pathway to
-// many abilities some consider unnatural; as such I refrain from practicing such dark magicks
-// for now.
-type Route = Vec<String>;
-type Connection = (String, String);
-
-// Checks if the route is one that contains the cave, returns the other cave if true.
-fn is_connection<'a, 'b>(node: &'a str, connection: &'b Connection) -> Option<&'b str> {
-    if connection.0 == node {
-        Some(&connection.1)
-    } else if connection.1 == node {
-        Some(&connection.0)
-    } else {
-        None
-    }
-}
-
-// true if cave is small and visited, false otherwise
-fn visited(node: &str, visited_smalls: &HashMap<String, u8>) -> bool {
-    let max_visit = if visited_smalls.iter().find(|(_, &v)| v == 2).is_some() {
-        1
-    } else {
-        2
-    };
-    (node == "start")
-        || (node.to_lowercase() == node && *visited_smalls.get(node).unwrap_or(&0) >= max_visit)
+// The fixed ends of every route, and how often each cave is on the current one.
+struct Search<'a> {
+    graph: &'a CaveGraph,
+    start: NodeId,
+    end: NodeId,
+    visits: Vec<u8>,
+    // Whether some small cave is already on the route twice.
+    revisited: bool,
 }
 
-fn find_routes(
-    current_node: &str,
-    map: &Vec<Connection>,
-    mut visited_smalls: HashMap<String, u8>,
-) -> Vec<Route> {
-    if current_node.to_lowercase() == current_node {
-        if !visited_smalls.contains_key(current_node) {
-            visited_smalls.insert(current_node.to_owned(), 0);
+impl Search<'_> {
+    // Counts routes from `node` to the end, backtracking so the visit counts are shared.
+    fn count_routes(&mut self, node: NodeId) -> u64 {
+        if node == self.end {
+            return 1;
+        }
+        let mut routes = 0;
+        for &next in self.graph.neighbours(node) {
+            if next == self.start {
+                continue;
+            }
+            let revisit = self.graph.is_small(next) && self.visits[next] > 0;
+            // One small cave may be visited twice; the rest only once.
+            if revisit && self.revisited {
+                continue;
+            }
+            self.visits[next] += 1;
+            self.revisited |= revisit;
+            routes += self.count_routes(next);
+            self.revisited &= !revisit;
+            self.visits[next] -= 1;
         }
-        *visited_smalls.get_mut(current_node).unwrap() += 1;
-    }
-    let mut result = Vec::new();
-    if current_node == "end" {
-        return vec![vec!["end".to_owned()]];
-    }
-    for next_node in map
-        .iter()
-        .filter_map(|c| is_connection(&current_node, c))
-        .filter(|n| !visited(n, &visited_smalls))
-        .collect::<Vec<&str>>()
-    {
-        let mut routes = find_routes(next_node, map, visited_smalls.clone());
         routes
-            .iter_mut()
-            .for_each(|route| route.insert(0, current_node.to_owned()));
-        result.extend(routes);
     }
-    result
 }
 
 fn main() -> Result<(), MainError> {
-    let map: Vec<Connection> = read_lines("./input")?
-        .flatten()
-        .map(|line| line.split('-').map(|s| s.to_owned()).collect())
-        .map(|mut list: Vec<String>| (list.swap_remove(0), list.swap_remove(0)))
-        .collect();
-    let result = find_routes("start", &map, HashMap::new());
-    println!("{}", result.len());
+    let graph = CaveGraph::parse(read_lines("./input")?.map_while(Result::ok))?;
+    let start = graph.id("start").ok_or("No start cave")?;
+    let end = graph.id("end").ok_or("No end cave")?;
+    let mut search = Search {
+        graph: &graph,
+        start,
+        end,
+        visits: vec![0; graph.cave_count()],
+        revisited: false,
+    };
+    println!("{}", search.count_routes(start));
     Ok(())
 }
 
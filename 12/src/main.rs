@@ -1,8 +1,9 @@
-use std::collections::HashSet;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
+use caves::{CaveGraph, NodeId};
+
 #[derive(Debug, Clone)]
 struct MainError {
     _msg: &'static str,
@@ -28,63 +29,45 @@ impl From<std::num::ParseIntError> for MainError {
     }
 }
 
-// Ideally we'd do this with a graph, but implementing a graph type in Rust is a pathway to
-// many abilities some consider unnatural; as such I refrain from practicing such dark magicks
-// for now.
-type Route = Vec<String>;
-type Connection = (String, String);
-
-// Checks if the route is one that contains the cave, returns the other cave if true.
-fn is_connection<'a, 'b>(node: &'a str, connection: &'b Connection) -> Option<&'b str> {
-    if connection.0 == node {
-        Some(&connection.1)
-    } else if connection.1 == node {
-        Some(&connection.0)
-    } else {
-        None
-    }
-}
-
-// true if cave is small and visited, false otherwise
-fn visited(node: &str, visited_smalls: &HashSet<String>) -> bool {
-    node.to_lowercase() == node && visited_smalls.contains(node)
+// The fixed ends of every route, and which caves are on the current one.
+struct Search<'a> {
+    graph: &'a CaveGraph,
+    start: NodeId,
+    end: NodeId,
+    visited: Vec<bool>,
 }
 
-fn find_routes(
-    current_node: &str,
-    map: &Vec<Connection>,
-    mut visited_smalls: HashSet<String>,
-) -> Vec<Route> {
-    if current_node.to_lowercase() == current_node {
-        visited_smalls.insert(current_node.to_owned());
-    }
-    let mut result = Vec::new();
-    if current_node == "end" {
-        return vec![vec!["end".to_owned()]];
-    }
-    for next_node in map
-        .iter()
-        .filter_map(|c| is_connection(&current_node, c))
-        .filter(|n| !visited(n, &visited_smalls))
-        .collect::<Vec<&str>>()
-    {
-        let mut routes = find_routes(next_node, map, visited_smalls.clone());
+impl Search<'_> {
+    // Counts routes from `node` to the end, backtracking so the visited marks are shared.
+    fn count_routes(&mut self, node: NodeId) -> u64 {
+        if node == self.end {
+            return 1;
+        }
+        let mut routes = 0;
+        for &next in self.graph.neighbours(node) {
+            // Small caves are only visited once.
+            if next == self.start || (self.graph.is_small(next) && self.visited[next]) {
+                continue;
+            }
+            self.visited[next] = true;
+            routes += self.count_routes(next);
+            self.visited[next] = false;
+        }
         routes
-            .iter_mut()
-            .for_each(|route| route.insert(0, current_node.to_owned()));
-        result.extend(routes);
     }
-    result
 }
 
 fn main() -> Result<(), MainError> {
-    let map: Vec<Connection> = read_lines("./input")?
-        .flatten()
-        .map(|line| line.split('-').map(|s| s.to_owned()).collect())
-        .map(|mut list: Vec<String>| (list.swap_remove(0), list.swap_remove(0)))
-        .collect();
-    let result = find_routes("start", &map, HashSet::new());
-    println!("{}", result.len());
+    let graph = CaveGraph::parse(read_lines("./input")?.map_while(Result::ok))?;
+    let start = graph.id("start").ok_or("No start cave")?;
+    let end = graph.id("end").ok_or("No end cave")?;
+    let mut search = Search {
+        graph: &graph,
+        start,
+        end,
+        visited: vec![false; graph.cave_count()],
+    };
+    println!("{}", search.count_routes(start));
     Ok(())
 }
 
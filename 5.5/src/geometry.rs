@@ -0,0 +1,160 @@
+// Counts overlapping vent cells straight from the line list, without walking every point.
+//
+// Every supported line lies on one of four families of parallel lines. Within a family a line
+// is identified by a constant, and a segment covers an interval of positions along it.
+use std::collections::{HashMap, HashSet};
+
+use crate::{Coord, Line};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum Family {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+}
+
+const FAMILIES: [Family; 4] = [
+    Family::Horizontal,
+    Family::Vertical,
+    Family::Diagonal,
+    Family::AntiDiagonal,
+];
+
+impl Family {
+    // Lines of this family are a * x + b * y = constant.
+    fn coefficients(self) -> (i64, i64) {
+        match self {
+            Family::Horizontal => (0, 1),
+            Family::Vertical => (1, 0),
+            Family::Diagonal => (-1, 1),
+            Family::AntiDiagonal => (1, 1),
+        }
+    }
+
+    // Returns the constant of the line through the point, and the point's position along it.
+    fn locate(self, (x, y): Coord) -> (i64, i64) {
+        let (a, b) = self.coefficients();
+        let position = if self == Family::Vertical { y } else { x };
+        (a * x + b * y, position)
+    }
+}
+
+struct Run {
+    family: Family,
+    constant: i64,
+    // Inclusive range of positions along the line.
+    lo: i64,
+    hi: i64,
+}
+
+impl Run {
+    fn new(start: Coord, end: Coord) -> Option<Run> {
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let family = if dy == 0 {
+            Family::Horizontal
+        } else if dx == 0 {
+            Family::Vertical
+        } else if dx == dy {
+            Family::Diagonal
+        } else if dx == -dy {
+            Family::AntiDiagonal
+        } else {
+            return None;
+        };
+        let (constant, a) = family.locate(start);
+        let (_, b) = family.locate(end);
+        Some(Run {
+            family,
+            constant,
+            lo: a.min(b),
+            hi: a.max(b),
+        })
+    }
+
+    // The cell where two runs from different families cross, if they share one.
+    fn crossing(&self, other: &Run) -> Option<Coord> {
+        let (a1, b1) = self.family.coefficients();
+        let (a2, b2) = other.family.coefficients();
+        let det = a1 * b2 - a2 * b1;
+        let x = self.constant * b2 - other.constant * b1;
+        let y = a1 * other.constant - a2 * self.constant;
+        // Diagonals of opposite parity cross between cells.
+        if x % det != 0 || y % det != 0 {
+            return None;
+        }
+        let point = (x / det, y / det);
+        let on_self = (self.lo..=self.hi).contains(&self.family.locate(point).1);
+        let on_other = (other.lo..=other.hi).contains(&other.family.locate(point).1);
+        (on_self && on_other).then_some(point)
+    }
+}
+
+// Whether count_overlaps can handle the line.
+pub fn supported(start: Coord, end: Coord) -> bool {
+    Run::new(start, end).is_some()
+}
+
+// Whether the position falls in one of the sorted, disjoint intervals.
+fn covered(intervals: &[(i64, i64)], position: i64) -> bool {
+    let index = intervals.partition_point(|&(_, hi)| hi < position);
+    intervals.get(index).is_some_and(|&(lo, _)| lo <= position)
+}
+
+// Counts the cells covered by at least two lines. Matches Board::count for the same lines.
+pub fn count_overlaps(lines: &[Line]) -> u64 {
+    let runs: Vec<Run> = lines
+        .iter()
+        .filter_map(|&(start, end)| Run::new(start, end))
+        .collect();
+    // Collinear overlaps, keyed by the line they lie on.
+    let mut shared: HashMap<(Family, i64), Vec<(i64, i64)>> = HashMap::new();
+    let mut crossings = HashSet::new();
+    for (i, a) in runs.iter().enumerate() {
+        for b in &runs[i + 1..] {
+            if a.family != b.family {
+                crossings.extend(a.crossing(b));
+            } else if a.constant == b.constant && a.lo.max(b.lo) <= a.hi.min(b.hi) {
+                shared
+                    .entry((a.family, a.constant))
+                    .or_default()
+                    .push((a.lo.max(b.lo), a.hi.min(b.hi)));
+            }
+        }
+    }
+    for intervals in shared.values_mut() {
+        intervals.sort_unstable();
+        let mut merged: Vec<(i64, i64)> = Vec::with_capacity(intervals.len());
+        for &(lo, hi) in intervals.iter() {
+            match merged.last_mut() {
+                Some(last) if lo <= last.1 => last.1 = last.1.max(hi),
+                _ => merged.push((lo, hi)),
+            }
+        }
+        *intervals = merged;
+    }
+    let mut count: u64 = shared
+        .values()
+        .flatten()
+        .map(|&(lo, hi)| lo.abs_diff(hi) + 1)
+        .sum();
+    // Overlaps from different families can only meet at crossings, so crossings are where
+    // cells get missed (on no overlap) or counted more than once (on several).
+    for point in crossings {
+        let overlaps = FAMILIES
+            .iter()
+            .filter(|&&family| {
+                let (constant, position) = family.locate(point);
+                shared
+                    .get(&(family, constant))
+                    .is_some_and(|intervals| covered(intervals, position))
+            })
+            .count() as u64;
+        if overlaps == 0 {
+            count += 1;
+        } else {
+            count -= overlaps - 1;
+        }
+    }
+    count
+}
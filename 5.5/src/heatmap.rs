@@ -0,0 +1,113 @@
+// Renders the board's overlap counts as a grayscale image, one pixel per cell.
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::{Board, DENSE_CELL_LIMIT};
+
+// How overlap counts map to brightness.
+#[derive(Clone, Copy)]
+pub enum Scale {
+    Linear,
+    Sqrt,
+    // Brings out sparse cells when a few hotspots dominate the counts.
+    Log,
+}
+
+impl Scale {
+    pub fn parse(name: &str) -> Option<Scale> {
+        match name {
+            "linear" => Some(Scale::Linear),
+            "sqrt" => Some(Scale::Sqrt),
+            "log" => Some(Scale::Log),
+            _ => None,
+        }
+    }
+
+    // Counts of `max` and above are white.
+    fn shade(self, count: u32, max: u32) -> u8 {
+        if max == 0 {
+            return 0;
+        }
+        let count = count.min(max) as f64;
+        let max = max as f64;
+        let level = match self {
+            Scale::Linear => count / max,
+            Scale::Sqrt => count.sqrt() / max.sqrt(),
+            Scale::Log => count.ln_1p() / max.ln_1p(),
+        };
+        (level * 255.0).round() as u8
+    }
+}
+
+pub struct Heatmap {
+    width: usize,
+    height: usize,
+    // Rows, then columns.
+    pixels: Vec<u8>,
+}
+
+impl Heatmap {
+    // Shades the board's bounding box. Without a `max`, the highest count is white.
+    pub fn new(board: &Board, scale: Scale, max: Option<u32>) -> io::Result<Heatmap> {
+        let cells = (board.min.0.abs_diff(board.max.0).saturating_add(1))
+            .saturating_mul(board.min.1.abs_diff(board.max.1).saturating_add(1));
+        if cells > DENSE_CELL_LIMIT {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "board too large for a heatmap",
+            ));
+        }
+        let width = (board.max.0 - board.min.0 + 1) as usize;
+        let height = (board.max.1 - board.min.1 + 1) as usize;
+        let counts: Vec<u32> = (board.min.1..=board.max.1)
+            .flat_map(|y| (board.min.0..=board.max.0).map(move |x| board.peek(x, y)))
+            .collect();
+        let max = max.unwrap_or_else(|| counts.iter().copied().max().unwrap_or(0));
+        Ok(Heatmap {
+            width,
+            height,
+            pixels: counts
+                .iter()
+                .map(|&count| scale.shade(count, max))
+                .collect(),
+        })
+    }
+
+    // Picks the image format from the file extension.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("pgm") => self.write_pgm(path),
+            #[cfg(feature = "png")]
+            Some("png") => self.write_png(path),
+            #[cfg(not(feature = "png"))]
+            Some("png") => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "PNG export needs the `png` feature",
+            )),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "heatmap path must end in .pgm or .png",
+            )),
+        }
+    }
+
+    fn write_pgm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        write!(file, "P5\n{} {}\n255\n", self.width, self.height)?;
+        file.write_all(&self.pixels)?;
+        file.flush()
+    }
+
+    #[cfg(feature = "png")]
+    fn write_png<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(io::Error::other)
+    }
+}
@@ -1,90 +1,195 @@
-#![feature(int_abs_diff)]
-#![feature(iter_zip)]
 #[macro_use]
 extern crate scan_fmt;
 
+mod geometry;
+mod heatmap;
+
+use std::collections::HashMap;
 use std::fmt;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
+use heatmap::{Heatmap, Scale};
+
+// Boards whose bounding box spans more cells than this are stored sparsely, and are too big to
+// draw as a heatmap.
+const DENSE_CELL_LIMIT: u64 = 1 << 24;
+
+type Coord = (i64, i64);
+type Line = (Coord, Coord);
+
 struct Square {
     count: u32,
 }
 
+// How lines that aren't horizontal, vertical or diagonal get drawn.
+#[derive(Clone, Copy)]
+enum Raster {
+    // Only the cells the line passes exactly through.
+    Lattice,
+    // A connected run of cells approximating the line.
+    Bresenham,
+}
+
+enum Storage {
+    // Rows, then columns, relative to the board's min corner.
+    Dense(Vec<Vec<Square>>),
+    Sparse(HashMap<Coord, u32>),
+}
+
 struct Board {
-    // Rows, then columns.
-    data: Vec<Vec<Square>>,
+    // Bounding box of every line on the board, inclusive.
+    min: Coord,
+    max: Coord,
+    storage: Storage,
+    raster: Raster,
 }
 
 impl Board {
+    // Creates a board big enough for the given lines, choosing dense storage when the
+    // bounding box is small enough to allocate outright.
+    fn new(lines: &[Line], raster: Raster) -> Board {
+        let points = || lines.iter().flat_map(|&(start, end)| [start, end]);
+        let min = (
+            points().map(|p| p.0).min().unwrap_or(0),
+            points().map(|p| p.1).min().unwrap_or(0),
+        );
+        let max = (
+            points().map(|p| p.0).max().unwrap_or(0),
+            points().map(|p| p.1).max().unwrap_or(0),
+        );
+        let cells = (min.0.abs_diff(max.0).saturating_add(1))
+            .saturating_mul(min.1.abs_diff(max.1).saturating_add(1));
+        let storage = if cells <= DENSE_CELL_LIMIT {
+            Storage::Dense(Vec::new())
+        } else {
+            Storage::Sparse(HashMap::new())
+        };
+        Board {
+            min,
+            max,
+            storage,
+            raster,
+        }
+    }
+
     // Get an element. Automatically allocate extra space if needed.
-    fn get(&mut self, x: usize, y: usize) -> &mut Square {
-        if y >= self.data.len() {
-            self.data.resize_with(y + 1, || Vec::new());
+    fn get(&mut self, x: i64, y: i64) -> &mut u32 {
+        match &mut self.storage {
+            Storage::Dense(data) => {
+                let x = (x - self.min.0) as usize;
+                let y = (y - self.min.1) as usize;
+                if y >= data.len() {
+                    data.resize_with(y + 1, Vec::new);
+                }
+                let row = &mut data[y];
+                if x >= row.len() {
+                    row.resize_with(x + 1, || Square { count: 0 });
+                }
+                &mut row[x].count
+            }
+            Storage::Sparse(data) => data.entry((x, y)).or_insert(0),
+        }
+    }
+
+    // Read an element without allocating.
+    fn peek(&self, x: i64, y: i64) -> u32 {
+        match &self.storage {
+            Storage::Dense(data) => data
+                .get((y - self.min.1) as usize)
+                .and_then(|row| row.get((x - self.min.0) as usize))
+                .map_or(0, |square| square.count),
+            Storage::Sparse(data) => *data.get(&(x, y)).unwrap_or(&0),
+        }
+    }
+
+    fn add_line(&mut self, start: Coord, end: Coord) {
+        match self.raster {
+            Raster::Lattice => self.add_lattice_line(start, end),
+            Raster::Bresenham => self.add_bresenham_line(start, end),
+        }
+    }
+
+    fn add_lattice_line(&mut self, start: Coord, end: Coord) {
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let steps = gcd(dx.abs(), dy.abs());
+        if steps == 0 {
+            *self.get(start.0, start.1) += 1;
+            return;
         }
-        let row = &mut self.data[y];
-        if x >= row.len() {
-            row.resize_with(x + 1, || Square { count: 0 });
+        let step = (dx / steps, dy / steps);
+        for i in 0..=steps {
+            *self.get(start.0 + i * step.0, start.1 + i * step.1) += 1;
         }
-        &mut row[x]
-    }
-
-    fn add_line(&mut self, start: (usize, usize), end: (usize, usize)) {
-        if start.0 == end.0 {
-            // Vertical line
-            let iter = if start.1 < end.1 {
-                start.1..end.1 + 1
-            } else {
-                end.1..start.1 + 1
-            };
-            iter.for_each(|row| self.get(start.0, row).count += 1);
-        } else if start.1 == end.1 {
-            // Horizontal line
-            let iter = if start.0 < end.0 {
-                start.0..end.0 + 1
-            } else {
-                end.0..start.0 + 1
-            };
-            iter.for_each(|col| self.get(col, start.1).count += 1);
-        } else if start.0.abs_diff(end.0) == start.1.abs_diff(end.1) {
-            // Diagonal line
-            // Box'ing is necessary because Range and Rev are different types.
-            let row_iter: Box<dyn std::iter::Iterator<Item = usize>> = if start.1 < end.1 {
-                Box::new(start.1..end.1 + 1)
-            } else {
-                Box::new((end.1..start.1 + 1).rev())
-            };
-            let col_iter: Box<dyn std::iter::Iterator<Item = usize>> = if start.0 < end.0 {
-                Box::new(start.0..end.0 + 1)
-            } else {
-                Box::new((end.0..start.0 + 1).rev())
-            };
-            std::iter::zip(col_iter, row_iter).for_each(|(col, row)| self.get(col, row).count += 1);
+    }
+
+    fn add_bresenham_line(&mut self, start: Coord, end: Coord) {
+        let (dx, dy) = ((end.0 - start.0).abs(), -(end.1 - start.1).abs());
+        let step = ((end.0 - start.0).signum(), (end.1 - start.1).signum());
+        let (mut x, mut y) = start;
+        let mut err = dx + dy;
+        loop {
+            *self.get(x, y) += 1;
+            if (x, y) == end {
+                break;
+            }
+            let err2 = 2 * err;
+            if err2 >= dy {
+                err += dy;
+                x += step.0;
+            }
+            if err2 <= dx {
+                err += dx;
+                y += step.1;
+            }
         }
     }
 
     fn count(&self) -> u32 {
-        self.data
-            .iter()
-            .flatten()
-            .filter(|square| square.count > 1)
-            .count() as u32
+        match &self.storage {
+            Storage::Dense(data) => data
+                .iter()
+                .flatten()
+                .filter(|square| square.count > 1)
+                .count() as u32,
+            Storage::Sparse(data) => data.values().filter(|&&count| count > 1).count() as u32,
+        }
     }
 }
 
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for row in &self.data {
-            for col in row {
-                write!(f, "{} ", col.count)?;
+        match &self.storage {
+            Storage::Dense(data) => {
+                for row in data {
+                    for col in row {
+                        write!(f, "{} ", col.count)?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+            Storage::Sparse(data) => {
+                for y in self.min.1..=self.max.1 {
+                    for x in self.min.0..=self.max.0 {
+                        write!(f, "{} ", data.get(&(x, y)).unwrap_or(&0))?;
+                    }
+                    writeln!(f)?;
+                }
             }
-            write!(f, "\n")?;
         }
         Ok(())
     }
 }
 
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
 #[derive(Debug, Clone)]
 struct MainError;
 
@@ -93,6 +198,11 @@ impl From<std::io::Error> for MainError {
         MainError {}
     }
 }
+impl From<std::num::ParseIntError> for MainError {
+    fn from(_: std::num::ParseIntError) -> Self {
+        MainError {}
+    }
+}
 impl From<scan_fmt::parse::ScanError> for MainError {
     fn from(_: scan_fmt::parse::ScanError) -> Self {
         MainError {}
@@ -100,13 +210,53 @@ impl From<scan_fmt::parse::ScanError> for MainError {
 }
 
 fn main() -> Result<(), MainError> {
-    let lines = read_lines("./input")?;
-    let mut board = Board { data: Vec::new() };
-
-    for line in lines {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    let value = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|i| args.get(i + 1))
+    };
+    // --geometry counts overlaps from the line list instead of drawing the board.
+    let geometry = flag("--geometry");
+    // --bresenham draws sloped lines as connected cells rather than exact lattice points.
+    let raster = if flag("--bresenham") {
+        Raster::Bresenham
+    } else {
+        Raster::Lattice
+    };
+    let mut lines = Vec::new();
+    for line in read_lines("./input")? {
         let line = line?;
-        let (x1, y1, x2, y2) = scan_fmt!(&line, "{},{} -> {},{}", usize, usize, usize, usize)?;
-        board.add_line((x1, y1), (x2, y2));
+        let (x1, y1, x2, y2) = scan_fmt!(&line, "{},{} -> {},{}", i64, i64, i64, i64)?;
+        lines.push(((x1, y1), (x2, y2)));
+    }
+    if geometry {
+        if lines
+            .iter()
+            .all(|&(start, end)| geometry::supported(start, end))
+        {
+            println!("{}", geometry::count_overlaps(&lines));
+            return Ok(());
+        }
+        eprintln!("--geometry only handles horizontal, vertical and diagonal lines; drawing the board instead");
+    }
+    let mut board = Board::new(&lines, raster);
+    for (start, end) in lines {
+        board.add_line(start, end);
+    }
+    // --heatmap <file.pgm|file.png> saves the counts as an image, shaded by --scale
+    // (linear, sqrt or log) with --max and above drawn white.
+    if let Some(path) = value("--heatmap") {
+        let scale = match value("--scale") {
+            Some(name) => Scale::parse(name).ok_or(MainError)?,
+            None => Scale::Linear,
+        };
+        let max = value("--max").map(|max| max.parse()).transpose()?;
+        if let Err(err) = Heatmap::new(&board, scale, max).and_then(|heatmap| heatmap.save(path)) {
+            eprintln!("Couldn't write heatmap: {}", err);
+            return Err(MainError);
+        }
     }
     println!("{}", board.count());
     Ok(())
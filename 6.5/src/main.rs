@@ -1,8 +1,11 @@
-use std::collections::VecDeque;
+mod matrix;
+
 use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, BufWriter, Write};
 use std::path::Path;
 
+use lanternfish::LanternfishModel;
+
 #[derive(Debug, Clone)]
 struct MainError;
 
@@ -18,29 +21,98 @@ impl From<&str> for MainError {
     }
 }
 
+impl From<std::num::ParseIntError> for MainError {
+    fn from(_: std::num::ParseIntError) -> Self {
+        MainError {}
+    }
+}
+
+// Reads `--name <value>` from the command line.
+fn option(args: &[String], name: &str) -> Result<Option<u64>, MainError> {
+    match args.iter().position(|arg| arg == name) {
+        Some(i) => Ok(Some(args.get(i + 1).ok_or("Missing value")?.parse()?)),
+        None => Ok(None),
+    }
+}
+
 fn main() -> Result<(), MainError> {
-    const VETERAN_DAYS: usize = 7;
-    const NEWBIE_DAYS: usize = 9;
-    let mut fishies_queue: VecDeque<u64> = VecDeque::new();
-    fishies_queue.resize(VETERAN_DAYS, 0);
-    let mut new_fishies_queue: VecDeque<u64> = VecDeque::new();
-    new_fishies_queue.resize(NEWBIE_DAYS - VETERAN_DAYS, 0);
-    read_lines("./input")?
+    // Defaults follow the puzzle; each can be overridden with e.g. `--days 80`.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let reset_timer = option(&args, "--reset")?.unwrap_or(6) as usize;
+    let newborn_timer = option(&args, "--newborn")?.unwrap_or(8) as usize;
+    let days = option(&args, "--days")?.unwrap_or(256);
+
+    let mut model = LanternfishModel::new(reset_timer, newborn_timer)?;
+    for value in read_lines("./input")?
         .next()
         .ok_or("No line")?? // Get the singular line from input
-        .split(",")
-        .map(|value| value.parse::<usize>().unwrap_or(0))
-        .for_each(|value| fishies_queue[value] += 1);
-    for _ in 0..256 {
-        let new_fishies = fishies_queue.pop_front().ok_or("FATAL")?;
-        new_fishies_queue.push_back(new_fishies);
-        let vet_fishies = new_fishies_queue.pop_front().ok_or("FATAL")?;
-        fishies_queue.push_back(new_fishies + vet_fishies); // new_fishies in this case means fish that just gave birth
-    }
-    println!(
-        "{}",
-        fishies_queue.iter().sum::<u64>() + new_fishies_queue.iter().sum::<u64>()
-    );
+        .split(',')
+    {
+        model.add_fish(value.parse::<usize>().unwrap_or(0))?;
+    }
+
+    if args.iter().any(|arg| arg == "--growth") {
+        println!("Growth rate: {:.6} per day", model.growth_rate());
+    }
+
+    // `--mod <prime>` and `--exact` skip ahead with matrix powers rather than stepping, for
+    // day counts far past where the total fits in a u64.
+    if let Some(modulus) = option(&args, "--mod")? {
+        if modulus == 0 {
+            return Err("Modulus must be positive".into());
+        }
+        let population = matrix::population(
+            &matrix::Modulo(modulus),
+            &model.timers(),
+            model.reset_timer(),
+            days,
+        );
+        println!("{}", population);
+        return Ok(());
+    }
+    if args.iter().any(|arg| arg == "--exact") {
+        #[cfg(feature = "bigint")]
+        {
+            let population =
+                matrix::population(&matrix::Exact, &model.timers(), model.reset_timer(), days);
+            println!("{}", population);
+            return Ok(());
+        }
+        #[cfg(not(feature = "bigint"))]
+        return Err("--exact needs the `bigint` feature".into());
+    }
+
+    // `--csv <path>` records the population on every day, with `-` meaning stdout.
+    let mut series: Option<Box<dyn Write>> = match args.iter().position(|arg| arg == "--csv") {
+        Some(i) => match args.get(i + 1).ok_or("Missing value")?.as_str() {
+            "-" => Some(Box::new(io::stdout())),
+            path => Some(Box::new(BufWriter::new(File::create(path)?))),
+        },
+        None => None,
+    };
+    if let Some(out) = &mut series {
+        let timers: Vec<String> = (0..model.timers().len())
+            .map(|timer| format!("timer_{}", timer))
+            .collect();
+        writeln!(out, "day,total,{}", timers.join(","))?;
+    }
+    for day in 0..=days {
+        if day > 0 {
+            model.step();
+        }
+        if let Some(out) = &mut series {
+            let timers: Vec<String> = model
+                .timers()
+                .iter()
+                .map(|count| count.to_string())
+                .collect();
+            writeln!(out, "{},{},{}", day, model.population(), timers.join(","))?;
+        }
+    }
+    if let Some(out) = &mut series {
+        out.flush()?;
+    }
+    println!("{}", model.population());
     Ok(())
 }
 
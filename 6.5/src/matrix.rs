@@ -0,0 +1,126 @@
+// Population after any number of days in O(log days), by raising the day-to-day transition
+// matrix to a power instead of stepping through every day.
+
+// The arithmetic the matrix is computed in, so results can be modular or exact.
+pub trait Arithmetic {
+    type Value: Clone;
+
+    fn value(&self, count: u64) -> Self::Value;
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+// Arithmetic modulo some number, typically a large prime.
+pub struct Modulo(pub u64);
+
+impl Arithmetic for Modulo {
+    type Value = u64;
+
+    fn value(&self, count: u64) -> u64 {
+        count % self.0
+    }
+
+    fn add(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 + *b as u128) % self.0 as u128) as u64
+    }
+
+    fn mul(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 * *b as u128) % self.0 as u128) as u64
+    }
+}
+
+// Exact arithmetic. Population grows exponentially, so this is only practical for days in the
+// thousands or millions, not 10^18.
+#[cfg(feature = "bigint")]
+pub struct Exact;
+
+#[cfg(feature = "bigint")]
+impl Arithmetic for Exact {
+    type Value = num_bigint::BigUint;
+
+    fn value(&self, count: u64) -> Self::Value {
+        count.into()
+    }
+
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Self::Value {
+        a + b
+    }
+
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Self::Value {
+        a * b
+    }
+}
+
+type Matrix<T> = Vec<Vec<T>>;
+
+fn multiply<A: Arithmetic>(
+    arith: &A,
+    a: &Matrix<A::Value>,
+    b: &Matrix<A::Value>,
+) -> Matrix<A::Value> {
+    let n = a.len();
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    (0..n).fold(arith.value(0), |sum, k| {
+                        arith.add(&sum, &arith.mul(&a[i][k], &b[k][j]))
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn apply<A: Arithmetic>(
+    arith: &A,
+    matrix: &Matrix<A::Value>,
+    vector: &[A::Value],
+) -> Vec<A::Value> {
+    matrix
+        .iter()
+        .map(|row| {
+            row.iter().zip(vector).fold(arith.value(0), |sum, (a, b)| {
+                arith.add(&sum, &arith.mul(a, b))
+            })
+        })
+        .collect()
+}
+
+/**
+ * Total population after `days`, given fish counts indexed by timer. Fish at timer 0 move to
+ * `reset_timer` and spawn a fish at the highest timer; everyone else counts down by one.
+ */
+pub fn population<A: Arithmetic>(
+    arith: &A,
+    timers: &[u64],
+    reset_timer: usize,
+    days: u64,
+) -> A::Value {
+    let n = timers.len();
+    let mut transition: Matrix<u64> = vec![vec![0; n]; n];
+    for timer in 1..n {
+        transition[timer - 1][timer] = 1;
+    }
+    transition[reset_timer][0] += 1;
+    transition[n - 1][0] += 1;
+
+    let mut power: Matrix<A::Value> = transition
+        .iter()
+        .map(|row| row.iter().map(|&cell| arith.value(cell)).collect())
+        .collect();
+    let mut state: Vec<A::Value> = timers.iter().map(|&count| arith.value(count)).collect();
+    let mut days = days;
+    while days > 0 {
+        if days & 1 == 1 {
+            state = apply(arith, &power, &state);
+        }
+        days >>= 1;
+        if days > 0 {
+            power = multiply(arith, &power, &power);
+        }
+    }
+    state
+        .iter()
+        .fold(arith.value(0), |sum, count| arith.add(&sum, count))
+}
@@ -2,9 +2,7 @@ use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
-struct Fish {
-    timer: u64,
-}
+use lanternfish::LanternfishModel;
 
 #[derive(Debug, Clone)]
 struct MainError;
@@ -21,28 +19,37 @@ impl From<&str> for MainError {
     }
 }
 
+impl From<std::num::ParseIntError> for MainError {
+    fn from(_: std::num::ParseIntError) -> Self {
+        MainError {}
+    }
+}
+
 fn main() -> Result<(), MainError> {
-    let mut fishies: Vec<Fish> = read_lines("./input")?
+    // Defaults follow the puzzle; each can be overridden with e.g. `--days 80`.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let option = |name: &str, default: usize| -> Result<usize, MainError> {
+        match args.iter().position(|arg| arg == name) {
+            Some(i) => Ok(args.get(i + 1).ok_or("Missing value")?.parse()?),
+            None => Ok(default),
+        }
+    };
+    let reset_timer = option("--reset", 6)?;
+    let newborn_timer = option("--newborn", 8)?;
+    let days = option("--days", 80)?;
+
+    let mut model = LanternfishModel::new(reset_timer, newborn_timer)?;
+    for value in read_lines("./input")?
         .next()
         .ok_or("No line")?? // Get the singular line from input
-        .split(",")
-        .map(|value| Fish {
-            timer: value.parse::<u64>().unwrap_or(0),
-        })
-        .collect();
-    for _ in 0..80 {
-        let mut new_fishies = 0;
-        fishies.iter_mut().for_each(|fish| {
-            if fish.timer == 0 {
-                fish.timer = 6;
-                new_fishies += 1;
-            } else {
-                fish.timer -= 1;
-            }
-        });
-        fishies.resize_with(fishies.len() + new_fishies, || Fish { timer: 8 });
+        .split(',')
+    {
+        model.add_fish(value.parse::<usize>().unwrap_or(0))?;
+    }
+    for _ in 0..days {
+        model.step();
     }
-    println!("{}", fishies.len());
+    println!("{}", model.population());
     Ok(())
 }
 
@@ -0,0 +1,127 @@
+// Arithmetic expressions in the distance `d`, for fuel costs given on the command line,
+// e.g. `d * d + 3 * d` or `(d + 1) ^ 2`.
+use std::convert::TryFrom;
+
+#[derive(Debug)]
+pub enum Expression {
+    Distance,
+    Number(u64),
+    Add(Box<Expression>, Box<Expression>),
+    Sub(Box<Expression>, Box<Expression>),
+    Mul(Box<Expression>, Box<Expression>),
+    Div(Box<Expression>, Box<Expression>),
+    Pow(Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Expression, &'static str> {
+        let tokens: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut parser = Parser { tokens, pos: 0 };
+        let expression = parser.sum()?;
+        if parser.pos != parser.tokens.len() {
+            return Err("Unexpected character in cost expression");
+        }
+        Ok(expression)
+    }
+
+    // None if the result doesn't fit in a u64. Subtraction stops at zero, and division by zero
+    // gives zero.
+    pub fn eval(&self, distance: u64) -> Option<u64> {
+        match self {
+            Expression::Distance => Some(distance),
+            Expression::Number(n) => Some(*n),
+            Expression::Add(a, b) => a.eval(distance)?.checked_add(b.eval(distance)?),
+            Expression::Sub(a, b) => Some(a.eval(distance)?.saturating_sub(b.eval(distance)?)),
+            Expression::Mul(a, b) => a.eval(distance)?.checked_mul(b.eval(distance)?),
+            Expression::Div(a, b) => Some(
+                a.eval(distance)?
+                    .checked_div(b.eval(distance)?)
+                    .unwrap_or(0),
+            ),
+            Expression::Pow(a, b) => {
+                let exponent = u32::try_from(b.eval(distance)?).ok()?;
+                a.eval(distance)?.checked_pow(exponent)
+            }
+        }
+    }
+}
+
+// Recursive descent, lowest precedence first: sum, product, power, atom.
+struct Parser {
+    tokens: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn sum(&mut self) -> Result<Expression, &'static str> {
+        let mut left = self.product()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.pos += 1;
+            let right = Box::new(self.product()?);
+            left = if op == '+' {
+                Expression::Add(Box::new(left), right)
+            } else {
+                Expression::Sub(Box::new(left), right)
+            };
+        }
+        Ok(left)
+    }
+
+    fn product(&mut self) -> Result<Expression, &'static str> {
+        let mut left = self.power()?;
+        while let Some(op @ ('*' | '/')) = self.peek() {
+            self.pos += 1;
+            let right = Box::new(self.power()?);
+            left = if op == '*' {
+                Expression::Mul(Box::new(left), right)
+            } else {
+                Expression::Div(Box::new(left), right)
+            };
+        }
+        Ok(left)
+    }
+
+    // Right associative, so 2^3^2 is 2^9.
+    fn power(&mut self) -> Result<Expression, &'static str> {
+        let base = self.atom()?;
+        if self.peek() == Some('^') {
+            self.pos += 1;
+            return Ok(Expression::Pow(Box::new(base), Box::new(self.power()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expression, &'static str> {
+        match self.peek() {
+            Some('d') => {
+                self.pos += 1;
+                Ok(Expression::Distance)
+            }
+            Some('(') => {
+                self.pos += 1;
+                let inner = self.sum()?;
+                if self.peek() != Some(')') {
+                    return Err("Unbalanced parentheses in cost expression");
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+                let digits: String = self.tokens[start..self.pos].iter().collect();
+                digits
+                    .parse()
+                    .map(Expression::Number)
+                    .map_err(|_| "Number too large in cost expression")
+            }
+            _ => Err("Expected a number, `d` or `(` in cost expression"),
+        }
+    }
+}
@@ -1,47 +1,269 @@
-#![feature(int_abs_diff)]
+mod expression;
+
+use std::fmt;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
-struct Fish {
-    timer: u64,
-}
+use expression::Expression;
 
 #[derive(Debug, Clone)]
-struct MainError;
+struct MainError {
+    _msg: String,
+}
 
 impl From<std::io::Error> for MainError {
     fn from(_: std::io::Error) -> Self {
-        MainError {}
+        MainError {
+            _msg: "IoError".to_owned(),
+        }
     }
 }
 
 impl From<&str> for MainError {
-    fn from(_: &str) -> Self {
-        MainError {}
+    fn from(msg: &str) -> Self {
+        MainError {
+            _msg: msg.to_owned(),
+        }
+    }
+}
+
+impl From<FuelOverflow> for MainError {
+    fn from(err: FuelOverflow) -> Self {
+        MainError {
+            _msg: err.to_string(),
+        }
+    }
+}
+
+// The total fuel for some position doesn't fit in a u64.
+#[derive(Debug)]
+struct FuelOverflow {
+    position: u32,
+}
+
+impl fmt::Display for FuelOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Fuel overflows a u64 when aligning at position {}",
+            self.position
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Alignment {
+    position: u32,
+    fuel: u64,
+}
+
+fn total_fuel<C: FuelCost + ?Sized>(
+    crabs: &[u32],
+    position: u32,
+    cost: &C,
+) -> Result<u64, FuelOverflow> {
+    crabs
+        .iter()
+        .try_fold(0u64, |sum, crab| {
+            sum.checked_add(cost.cost(crab.abs_diff(position))?)
+        })
+        .ok_or(FuelOverflow { position })
+}
+
+// Like `total_fuel`, but summed wide enough that only a single crab's cost can overflow, in
+// which case the total saturates.
+fn wide_fuel<C: FuelCost + ?Sized>(crabs: &[u32], position: u32, cost: &C) -> u128 {
+    crabs.iter().fold(0u128, |sum, crab| {
+        let fuel = cost.cost(crab.abs_diff(position));
+        sum.saturating_add(fuel.map_or(u128::MAX, |fuel| fuel as u128))
+    })
+}
+
+// Cheapest of the candidate positions, or None if there are no candidates. Positions whose total
+// overflows lose to any that fit; it's only an error if the cheapest one overflows too.
+fn best_of<C: FuelCost + ?Sized>(
+    crabs: &[u32],
+    candidates: impl Iterator<Item = u32>,
+    cost: &C,
+) -> Result<Option<Alignment>, FuelOverflow> {
+    let mut best: Option<Result<Alignment, FuelOverflow>> = None;
+    for position in candidates {
+        let fuel = total_fuel(crabs, position, cost);
+        let better = match (&best, &fuel) {
+            (None, _) | (Some(Err(_)), Ok(_)) => true,
+            (Some(Ok(best)), Ok(fuel)) => *fuel < best.fuel,
+            _ => false,
+        };
+        if better {
+            best = Some(fuel.map(|fuel| Alignment { position, fuel }));
+        }
+    }
+    best.transpose()
+}
+
+// Ternary search over the crab range. Valid for any cost that is convex in the distance, since
+// the total is then convex in the position.
+fn align_convex<C: FuelCost + ?Sized>(
+    crabs: &[u32],
+    cost: &C,
+) -> Result<Option<Alignment>, FuelOverflow> {
+    let (min, max) = match (crabs.iter().min(), crabs.iter().max()) {
+        (Some(&min), Some(&max)) => (min, max),
+        _ => return Ok(None),
+    };
+    // Two probes that both overflow don't say which way the optimum is, so first narrow the
+    // range to positions where every crab's cost fits. For a convex cost the distances that fit
+    // run from 0 up to `fits`, found by bisection, so those are the positions within `fits` of
+    // every crab.
+    if cost.cost(0).is_none() {
+        return best_of(crabs, min..=max, cost);
+    }
+    let (mut fits, mut overflows) = (0, (max - min).saturating_add(1));
+    while overflows - fits > 1 {
+        let mid = fits + (overflows - fits) / 2;
+        if cost.cost(mid).is_some() {
+            fits = mid;
+        } else {
+            overflows = mid;
+        }
+    }
+    let (mut low, mut high) = (max.saturating_sub(fits).max(min), min.saturating_add(fits));
+    if low > high {
+        return Err(FuelOverflow {
+            position: min + (max - min) / 2,
+        });
+    }
+    while high - low > 2 {
+        let third = (high - low) / 3;
+        if wide_fuel(crabs, low + third, cost) > wide_fuel(crabs, high - third, cost) {
+            low += third + 1;
+        } else {
+            high -= third;
+        }
+    }
+    best_of(crabs, low..=high, cost)
+}
+
+trait FuelCost {
+    // Fuel for one crab to move `distance` steps, or None if it doesn't fit in a u64.
+    fn cost(&self, distance: u32) -> Option<u64>;
+
+    // Whether the cost is convex over distances up to `max_distance`, checked by making sure
+    // each step costs at least as much extra as the one before. Costs that overflow count as
+    // infinite, so a cost that grows out of range is still convex.
+    fn is_convex(&self, max_distance: u32) -> bool {
+        (1..max_distance).all(
+            |d| match (self.cost(d - 1), self.cost(d), self.cost(d + 1)) {
+                (Some(prev), Some(here), Some(next)) => {
+                    here as u128 * 2 <= prev as u128 + next as u128
+                }
+                (prev, None, next) => prev.is_none() || next.is_none(),
+                _ => true,
+            },
+        )
+    }
+
+    // Defaults to a ternary search when the cost is convex, otherwise tries every position.
+    fn align(&self, crabs: &[u32]) -> Result<Option<Alignment>, FuelOverflow> {
+        let (low, high) = match (crabs.iter().min(), crabs.iter().max()) {
+            (Some(&low), Some(&high)) => (low, high),
+            _ => return Ok(None),
+        };
+        if self.is_convex(high - low) {
+            align_convex(crabs, self)
+        } else {
+            best_of(crabs, low..=high, self)
+        }
+    }
+}
+
+struct Linear;
+
+impl FuelCost for Linear {
+    fn cost(&self, distance: u32) -> Option<u64> {
+        Some(distance as u64)
+    }
+
+    fn is_convex(&self, _: u32) -> bool {
+        true
+    }
+
+    // Any median minimises the total distance.
+    fn align(&self, crabs: &[u32]) -> Result<Option<Alignment>, FuelOverflow> {
+        let mut sorted = crabs.to_vec();
+        sorted.sort_unstable();
+        let median = match sorted.get(sorted.len() / 2) {
+            Some(&median) => median,
+            None => return Ok(None),
+        };
+        best_of(crabs, std::iter::once(median), self)
+    }
+}
+
+struct Triangular;
+
+impl FuelCost for Triangular {
+    fn cost(&self, distance: u32) -> Option<u64> {
+        let distance = distance as u64;
+        Some(distance.checked_mul(distance + 1)? / 2)
+    }
+
+    fn is_convex(&self, _: u32) -> bool {
+        true
+    }
+
+    // The real-valued optimum is within half a step of the mean, so only the integers around
+    // it need checking.
+    fn align(&self, crabs: &[u32]) -> Result<Option<Alignment>, FuelOverflow> {
+        if crabs.is_empty() {
+            return Ok(None);
+        }
+        let mean = crabs.iter().map(|&crab| crab as f64).sum::<f64>() / crabs.len() as f64;
+        let low = (mean - 0.5).floor().max(0.0) as u32;
+        let high = (mean + 0.5).ceil() as u32;
+        best_of(crabs, low..=high, self)
+    }
+}
+
+struct Quadratic;
+
+impl FuelCost for Quadratic {
+    fn cost(&self, distance: u32) -> Option<u64> {
+        (distance as u64).checked_mul(distance as u64)
+    }
+
+    fn is_convex(&self, _: u32) -> bool {
+        true
+    }
+}
+
+impl FuelCost for Expression {
+    fn cost(&self, distance: u32) -> Option<u64> {
+        self.eval(distance as u64)
     }
 }
 
 fn main() -> Result<(), MainError> {
     let line = read_lines("./input")?.next().ok_or("No line")??; // Get the singular line from input
-    let mut values: Vec<u32> = line
-        .split(",")
+    let values: Vec<u32> = line
+        .split(',')
         .map(|value| value.parse::<u32>().unwrap_or(0))
         .collect();
-    let mut min_fuel = u32::MAX;
-    for position in 0..*values.iter().max().ok_or("ERROR")? {
-        let fuel = values
-            .iter()
-            .map(|value| {
-                let diff = value.abs_diff(position);
-                diff * (diff + 1) / 2
-            })
-            .sum();
-        if fuel < min_fuel {
-            min_fuel = fuel;
-        }
-    }
-    println!("{}", min_fuel);
+    // `--cost` takes linear (part 1), triangular (part 2, the default), quadratic, or an
+    // expression in the distance d such as "d^3 + 2*d".
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let cost: Box<dyn FuelCost> = match args.iter().position(|arg| arg == "--cost") {
+        None => Box::new(Triangular),
+        Some(i) => match args.get(i + 1).ok_or("Missing value")?.as_str() {
+            "linear" => Box::new(Linear),
+            "triangular" => Box::new(Triangular),
+            "quadratic" => Box::new(Quadratic),
+            expression => Box::new(Expression::parse(expression)?),
+        },
+    };
+    let alignment = cost.align(&values)?.ok_or("No crabs")?;
+    println!("{} (position {})", alignment.fuel, alignment.position);
     Ok(())
 }
 
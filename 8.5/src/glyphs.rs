@@ -0,0 +1,104 @@
+// Glyph tables: which segments light up for each symbol a display can show.
+//
+// Table files have one glyph per line, the symbol then its lit segments, e.g. `7 acf`. Blank
+// lines and lines starting with `#` are skipped.
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::solver::{self, Glyph};
+
+// Lit segments of each decimal digit, with segments lettered a to g like so:
+//  aaaa
+// b    c
+//  dddd
+// e    f
+//  gggg
+const DIGITS: [(char, &str); 10] = [
+    ('0', "abcefg"),
+    ('1', "cf"),
+    ('2', "acdeg"),
+    ('3', "acdfg"),
+    ('4', "bcdf"),
+    ('5', "abdfg"),
+    ('6', "abdefg"),
+    ('7', "acf"),
+    ('8', "abcdefg"),
+    ('9', "abcdfg"),
+];
+
+const NUMERALS: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+
+pub struct GlyphTable {
+    glyphs: Vec<Glyph>,
+}
+
+impl GlyphTable {
+    // The ten decimal digits on a seven-segment display.
+    pub fn decimal() -> GlyphTable {
+        GlyphTable {
+            glyphs: DIGITS
+                .iter()
+                .map(|&(symbol, segments)| Glyph {
+                    symbol,
+                    segments: solver::mask(segments).unwrap_or(0),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<GlyphTable, &'static str> {
+        let text = fs::read_to_string(path).map_err(|_| "Can't read glyph table")?;
+        GlyphTable::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<GlyphTable, &'static str> {
+        let mut glyphs = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let mut symbol = fields.next().ok_or("Missing glyph symbol")?.chars();
+            let segments = fields.next().ok_or("Missing glyph segments")?;
+            glyphs.push(Glyph {
+                symbol: symbol.next().ok_or("Missing glyph symbol")?,
+                segments: solver::mask(segments).ok_or("Segments must be lowercase letters")?,
+            });
+            if symbol.next().is_some() || fields.next().is_some() {
+                return Err("Glyph lines must be a symbol and its segments");
+            }
+        }
+        let symbols: HashSet<char> = glyphs.iter().map(|glyph| glyph.symbol).collect();
+        let shapes: HashSet<u32> = glyphs.iter().map(|glyph| glyph.segments).collect();
+        if symbols.len() != glyphs.len() || shapes.len() != glyphs.len() {
+            return Err("Glyph symbols and segments must be unique");
+        }
+        Ok(GlyphTable { glyphs })
+    }
+
+    pub fn glyphs(&self) -> &[Glyph] {
+        &self.glyphs
+    }
+
+    // Whether only one glyph lights this many segments, so it can be read without unscrambling.
+    pub fn is_easy(&self, segment_count: usize) -> bool {
+        self.glyphs
+            .iter()
+            .filter(|glyph| glyph.segments.count_ones() as usize == segment_count)
+            .count()
+            == 1
+    }
+
+    // If the symbols are exactly the digits of some base, e.g. 0-9 and A-F, that base.
+    pub fn radix(&self) -> Option<u32> {
+        let mut symbols: Vec<char> = self
+            .glyphs
+            .iter()
+            .map(|glyph| glyph.symbol.to_ascii_lowercase())
+            .collect();
+        symbols.sort_unstable();
+        let numerals: Vec<char> = NUMERALS.chars().take(symbols.len()).collect();
+        (symbols.len() > 1 && symbols == numerals).then_some(symbols.len() as u32)
+    }
+}
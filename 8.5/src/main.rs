@@ -1,9 +1,17 @@
+mod glyphs;
+mod report;
+mod solver;
+
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
+use glyphs::GlyphTable;
+use report::LineReport;
+use solver::{SolveError, Wiring};
+
 #[derive(Debug, Clone)]
 struct MainError {
     _msg: &'static str,
@@ -41,6 +49,17 @@ impl From<&'static str> for MainError {
     }
 }
 
+impl From<SolveError> for MainError {
+    fn from(err: SolveError) -> Self {
+        MainError {
+            _msg: match err {
+                SolveError::NoConsistentMapping => "No consistent mapping",
+                SolveError::AmbiguousMapping => "Ambiguous mapping",
+            },
+        }
+    }
+}
+
 impl From<std::num::ParseIntError> for MainError {
     fn from(_: std::num::ParseIntError) -> Self {
         MainError {
@@ -49,103 +68,181 @@ impl From<std::num::ParseIntError> for MainError {
     }
 }
 
-fn main() -> Result<(), MainError> {
-    let lines = read_lines("./input")?.flatten();
-    let mut sum = 0;
-    for line in lines {
-        // Map of digit to their segments.
-        let mut digit_to_segment = HashMap::new();
-        // Map of digit to their frequency within the 10-num cycle.
-        let mut digit_to_freq = HashMap::new();
-        // We use 1 and 4 to disambiguate the segments with the same frequency.
-        let mut digit_in_one = HashSet::new();
-        let mut digit_in_four = HashSet::new();
-        let mut line_split = line.split("|");
-        let patterns = line_split.next().ok_or("ERROR")?.split(" ");
-        for pattern in patterns {
-            let length = pattern.len();
-            for c in pattern.chars() {
-                if !digit_to_freq.contains_key(&c) {
-                    digit_to_freq.insert(c, 0);
-                }
-                *digit_to_freq.get_mut(&c).unwrap() += 1;
-                if length == 2 {
-                    digit_in_one.insert(c);
-                } else if length == 4 {
-                    digit_in_four.insert(c);
-                }
+// Decodes an output using how often each segment lights up across the ten digits, plus the
+// patterns for 1 and 4.
+fn decode_by_frequency(patterns: &[&str], outputs: &[&str]) -> Result<u32, MainError> {
+    // Map of digit to their segments.
+    let mut digit_to_segment = HashMap::new();
+    // Map of digit to their frequency within the 10-num cycle.
+    let mut digit_to_freq = HashMap::new();
+    // We use 1 and 4 to disambiguate the segments with the same frequency.
+    let mut digit_in_one = HashSet::new();
+    let mut digit_in_four = HashSet::new();
+    for pattern in patterns {
+        let length = pattern.len();
+        for c in pattern.chars() {
+            *digit_to_freq.entry(c).or_insert(0) += 1;
+            if length == 2 {
+                digit_in_one.insert(c);
+            } else if length == 4 {
+                digit_in_four.insert(c);
             }
         }
-        // Now we assign digit to segments
-        digit_to_freq.iter().for_each(|(digit, frequency)| {
-            match frequency {
-                4 => digit_to_segment.insert(*digit, Segment::BottomLeft),
-                6 => digit_to_segment.insert(*digit, Segment::TopLeft),
-                7 => {
-                    if digit_in_four.contains(digit) {
-                        digit_to_segment.insert(*digit, Segment::Middle)
-                    } else {
-                        digit_to_segment.insert(*digit, Segment::Bottom)
-                    }
+    }
+    // Now we assign digit to segments
+    digit_to_freq.iter().for_each(|(digit, frequency)| {
+        match frequency {
+            4 => digit_to_segment.insert(*digit, Segment::BottomLeft),
+            6 => digit_to_segment.insert(*digit, Segment::TopLeft),
+            7 => {
+                if digit_in_four.contains(digit) {
+                    digit_to_segment.insert(*digit, Segment::Middle)
+                } else {
+                    digit_to_segment.insert(*digit, Segment::Bottom)
                 }
-                8 => {
-                    if digit_in_one.contains(digit) {
-                        digit_to_segment.insert(*digit, Segment::TopRight)
-                    } else {
-                        digit_to_segment.insert(*digit, Segment::Top)
-                    }
+            }
+            8 => {
+                if digit_in_one.contains(digit) {
+                    digit_to_segment.insert(*digit, Segment::TopRight)
+                } else {
+                    digit_to_segment.insert(*digit, Segment::Top)
                 }
-                9 => digit_to_segment.insert(*digit, Segment::BottomRight),
-                _ => None,
-            };
-        });
-        let outputs = line_split.next().ok_or("ERROR")?.split(" ");
-        let mut char_arr = Vec::new();
-        for output in outputs {
-            if output.len() == 0 {
-                continue;
             }
-            match output.len() {
-                2 => char_arr.push('1'),
-                3 => char_arr.push('7'),
-                4 => char_arr.push('4'),
-                5 => {
-                    if output
-                        .chars()
-                        .any(|digit| *digit_to_segment.get(&digit).unwrap() == Segment::TopLeft)
-                    {
-                        char_arr.push('5');
-                    } else if output
-                        .chars()
-                        .any(|digit| *digit_to_segment.get(&digit).unwrap() == Segment::BottomLeft)
-                    {
-                        char_arr.push('2');
-                    } else {
-                        char_arr.push('3');
-                    }
+            9 => digit_to_segment.insert(*digit, Segment::BottomRight),
+            _ => None,
+        };
+    });
+    let mut char_arr = Vec::new();
+    for output in outputs {
+        match output.len() {
+            2 => char_arr.push('1'),
+            3 => char_arr.push('7'),
+            4 => char_arr.push('4'),
+            5 => {
+                if output
+                    .chars()
+                    .any(|digit| digit_to_segment.get(&digit) == Some(&Segment::TopLeft))
+                {
+                    char_arr.push('5');
+                } else if output
+                    .chars()
+                    .any(|digit| digit_to_segment.get(&digit) == Some(&Segment::BottomLeft))
+                {
+                    char_arr.push('2');
+                } else {
+                    char_arr.push('3');
                 }
-                6 => {
-                    if output
-                        .chars()
-                        .all(|digit| *digit_to_segment.get(&digit).unwrap() != Segment::Middle)
-                    {
-                        char_arr.push('0');
-                    } else if output
-                        .chars()
-                        .any(|digit| *digit_to_segment.get(&digit).unwrap() == Segment::BottomLeft)
-                    {
-                        char_arr.push('6');
-                    } else {
-                        char_arr.push('9');
-                    }
+            }
+            6 => {
+                if output
+                    .chars()
+                    .all(|digit| digit_to_segment.get(&digit) != Some(&Segment::Middle))
+                {
+                    char_arr.push('0');
+                } else if output
+                    .chars()
+                    .any(|digit| digit_to_segment.get(&digit) == Some(&Segment::BottomLeft))
+                {
+                    char_arr.push('6');
+                } else {
+                    char_arr.push('9');
                 }
-                7 => char_arr.push('8'),
-                _ => (),
-            };
+            }
+            7 => char_arr.push('8'),
+            _ => (),
+        };
+    }
+    Ok(char_arr.iter().collect::<String>().parse::<u32>()?)
+}
+
+// Decodes an output by searching for the one wiring that makes every pattern a glyph.
+fn decode_by_search(
+    table: &GlyphTable,
+    patterns: &[&str],
+    outputs: &[&str],
+) -> Result<(Wiring, String), MainError> {
+    let masks = patterns
+        .iter()
+        .map(|pattern| solver::mask(pattern))
+        .collect::<Option<Vec<u32>>>()
+        .ok_or("Pattern isn't all lowercase letters")?;
+    let wiring = solver::solve(table.glyphs(), &masks)?;
+    let mut char_arr = Vec::new();
+    for output in outputs {
+        let wires = solver::mask(output).ok_or("Output isn't all lowercase letters")?;
+        char_arr.push(
+            wiring
+                .decode(table.glyphs(), wires)
+                .ok_or("Output isn't a known glyph")?,
+        );
+    }
+    Ok((wiring, char_arr.iter().collect()))
+}
+
+fn main() -> Result<(), MainError> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // --glyphs <file> swaps the decimal digits for another glyph table, see glyphs/.
+    let table = match args.iter().position(|arg| arg == "--glyphs") {
+        Some(i) => GlyphTable::load(args.get(i + 1).ok_or("Missing value")?)?,
+        None => GlyphTable::decimal(),
+    };
+    // --check also decodes every line by segment frequency and fails if the two disagree.
+    let check = args.iter().any(|arg| arg == "--check");
+    if check && args.iter().any(|arg| arg == "--glyphs") {
+        return Err("--check only works with the decimal digits".into());
+    }
+    // --report table|json prints the recovered wiring and value of every line.
+    let report = match args.iter().position(|arg| arg == "--report") {
+        Some(i) => Some(
+            args.get(i + 1)
+                .and_then(|format| report::Format::parse(format))
+                .ok_or("--report takes table or json")?,
+        ),
+        None => None,
+    };
+    let mut reports = Vec::new();
+    let lines = read_lines("./input")?.map_while(Result::ok);
+    // Outputs are summed when the glyphs are the digits of some base, and listed otherwise.
+    let radix = table.radix();
+    let mut sum = 0;
+    for (i, line) in lines.enumerate() {
+        let mut line_split = line.split('|');
+        let patterns: Vec<&str> = line_split
+            .next()
+            .ok_or("ERROR")?
+            .split_whitespace()
+            .collect();
+        let outputs: Vec<&str> = line_split
+            .next()
+            .ok_or("ERROR")?
+            .split_whitespace()
+            .collect();
+        let (wiring, decoded) = decode_by_search(&table, &patterns, &outputs)?;
+        if check && decode_by_frequency(&patterns, &outputs)? != decoded.parse::<u32>()? {
+            return Err("Decoders disagree".into());
         }
-        sum += char_arr.iter().collect::<String>().parse::<u32>()?;
+        if let Some(radix) = radix {
+            sum += u64::from_str_radix(&decoded, radix)?;
+        }
+        if report.is_some() {
+            reports.push(LineReport {
+                line: i + 1,
+                wiring: wiring.describe(),
+                easy: outputs
+                    .iter()
+                    .filter(|output| table.is_easy(output.len()))
+                    .count(),
+                value: decoded,
+            });
+        } else if radix.is_none() {
+            println!("{}", decoded);
+        }
+    }
+    if let Some(format) = report {
+        format.print(&reports, radix.map(|_| sum));
+    } else if radix.is_some() {
+        println!("{}", sum);
     }
-    println!("{}", sum);
     Ok(())
 }
 
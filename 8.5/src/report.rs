@@ -0,0 +1,96 @@
+// Per-line decoding reports, for tracking down lines that decode wrongly.
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Option<Format> {
+        match name {
+            "table" => Some(Format::Table),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    pub fn print(self, reports: &[LineReport], sum: Option<u64>) {
+        match self {
+            Format::Table => print_table(reports, sum),
+            Format::Json => print_json(reports, sum),
+        }
+    }
+}
+
+pub struct LineReport {
+    // 1-based line number in the input.
+    pub line: usize,
+    pub wiring: String,
+    pub value: String,
+    // Outputs that can be told apart by length alone, e.g. 1, 4, 7 and 8.
+    pub easy: usize,
+}
+
+fn print_table(reports: &[LineReport], sum: Option<u64>) {
+    let wiring_width = reports
+        .iter()
+        .map(|report| report.wiring.len())
+        .max()
+        .unwrap_or(0)
+        .max("wiring".len());
+    let value_width = reports
+        .iter()
+        .map(|report| report.value.len())
+        .max()
+        .unwrap_or(0)
+        .max("value".len());
+    println!(
+        "{:>5}  {:<wiring_width$}  {:<value_width$}  easy",
+        "line", "wiring", "value"
+    );
+    for report in reports {
+        println!(
+            "{:>5}  {:<wiring_width$}  {:<value_width$}  {}",
+            report.line, report.wiring, report.value, report.easy
+        );
+    }
+    if let Some(sum) = sum {
+        println!("sum: {}", sum);
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn print_json(reports: &[LineReport], sum: Option<u64>) {
+    println!("{{");
+    println!("  \"lines\": [");
+    for (i, report) in reports.iter().enumerate() {
+        println!(
+            "    {{\"line\": {}, \"wiring\": {}, \"value\": {}, \"easy\": {}}}{}",
+            report.line,
+            json_string(&report.wiring),
+            json_string(&report.value),
+            report.easy,
+            if i + 1 < reports.len() { "," } else { "" }
+        );
+    }
+    println!("  ],");
+    match sum {
+        Some(sum) => println!("  \"sum\": {}", sum),
+        None => println!("  \"sum\": null"),
+    }
+    println!("}}");
+}
@@ -0,0 +1,200 @@
+// General display unscrambler. Treats the wiring as a constraint problem: find every
+// wire-to-segment permutation under which each observed pattern lights up a known glyph.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveError {
+    NoConsistentMapping,
+    AmbiguousMapping,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::NoConsistentMapping => write!(f, "no consistent mapping"),
+            SolveError::AmbiguousMapping => write!(f, "ambiguous mapping"),
+        }
+    }
+}
+
+// Letters as a bit mask, 'a' being bit 0. None if there's anything but lowercase letters.
+pub fn mask(letters: &str) -> Option<u32> {
+    letters.chars().try_fold(0, |mask, c| {
+        if c.is_ascii_lowercase() {
+            Some(mask | 1 << (c as u32 - 'a' as u32))
+        } else {
+            None
+        }
+    })
+}
+
+pub struct Glyph {
+    pub symbol: char,
+    // Lit segments, as a mask over the segment letters.
+    pub segments: u32,
+}
+
+// Which segment each wire drives, indexed by wire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wiring {
+    segments: Vec<u32>,
+}
+
+impl Wiring {
+    // Segments lit by a pattern of wires.
+    pub fn translate(&self, wires: u32) -> u32 {
+        self.segments
+            .iter()
+            .enumerate()
+            .filter(|(wire, _)| wires & 1 << wire != 0)
+            .map(|(_, &segment)| 1 << segment)
+            .sum()
+    }
+
+    // Each wire with the segment it drives, e.g. "a:d b:e c:a".
+    pub fn describe(&self) -> String {
+        self.segments
+            .iter()
+            .enumerate()
+            .map(|(wire, &segment)| {
+                format!(
+                    "{}:{}",
+                    (b'a' + wire as u8) as char,
+                    (b'a' + segment as u8) as char
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    pub fn decode(&self, glyphs: &[Glyph], wires: u32) -> Option<char> {
+        let segments = self.translate(wires);
+        glyphs
+            .iter()
+            .find(|glyph| glyph.segments == segments)
+            .map(|glyph| glyph.symbol)
+    }
+}
+
+struct Search<'a> {
+    glyphs: &'a [Glyph],
+    patterns: &'a [u32],
+    all_segments: u32,
+    // Segment chosen for each wire, if any yet.
+    assigned: Vec<Option<u32>>,
+    solutions: Vec<Wiring>,
+}
+
+impl Search<'_> {
+    /**
+     * `candidates` holds, for each pattern, a mask of the glyphs it could still be. Propagates
+     * those into the segments each unassigned wire could drive, then branches on the wire with
+     * the fewest options. Stops once a second solution turns up, as that's enough to call it
+     * ambiguous.
+     */
+    fn run(&mut self, candidates: &[u64], used: u32) {
+        if self.solutions.len() > 1 {
+            return;
+        }
+        // Segments lit in some candidate glyph, and segments dark in some candidate glyph.
+        let reach: Vec<(u32, u32)> = candidates
+            .iter()
+            .map(|&mask| {
+                self.glyphs
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| mask & 1 << i != 0)
+                    .fold((0, 0), |(lit, dark), (_, glyph)| {
+                        (
+                            lit | glyph.segments,
+                            dark | self.all_segments & !glyph.segments,
+                        )
+                    })
+            })
+            .collect();
+        let mut branch: Option<(usize, u32)> = None;
+        for wire in (0..self.assigned.len()).filter(|&wire| self.assigned[wire].is_none()) {
+            let domain = self.patterns.iter().zip(&reach).fold(
+                self.all_segments & !used,
+                |domain, (&pattern, &(lit, dark))| {
+                    domain & if pattern & 1 << wire != 0 { lit } else { dark }
+                },
+            );
+            if domain == 0 {
+                return;
+            }
+            if branch.is_none_or(|(_, best)| domain.count_ones() < best.count_ones()) {
+                branch = Some((wire, domain));
+            }
+        }
+        let (wire, domain) = match branch {
+            Some(branch) => branch,
+            None => {
+                self.solutions.push(Wiring {
+                    segments: self.assigned.iter().map(|s| s.unwrap_or(0)).collect(),
+                });
+                return;
+            }
+        };
+        for segment in (0..u32::BITS).filter(|segment| domain & 1 << segment != 0) {
+            // Keep only glyphs that agree with this wire's pattern membership.
+            let narrowed: Vec<u64> = self
+                .patterns
+                .iter()
+                .zip(candidates)
+                .map(|(&pattern, &mask)| {
+                    let lit = pattern & 1 << wire != 0;
+                    self.glyphs
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, glyph)| {
+                            mask & 1 << i != 0 && (glyph.segments & 1 << segment != 0) == lit
+                        })
+                        .fold(0, |mask, (i, _)| mask | 1 << i)
+                })
+                .collect();
+            if narrowed.contains(&0) {
+                continue;
+            }
+            self.assigned[wire] = Some(segment);
+            self.run(&narrowed, used | 1 << segment);
+            self.assigned[wire] = None;
+        }
+    }
+}
+
+/**
+ * Finds the only wiring under which every pattern is a glyph. Patterns and glyphs are masks over
+ * the same letters, one wire per segment. Supports up to 64 glyphs.
+ */
+pub fn solve(glyphs: &[Glyph], patterns: &[u32]) -> Result<Wiring, SolveError> {
+    let all_segments = glyphs.iter().fold(0, |all, glyph| all | glyph.segments);
+    let wire_count = (u32::BITS - all_segments.leading_zeros()) as usize;
+    if glyphs.len() > 64 || patterns.iter().any(|&pattern| pattern & !all_segments != 0) {
+        return Err(SolveError::NoConsistentMapping);
+    }
+    // To start with, a pattern could be any glyph with as many segments.
+    let candidates: Vec<u64> = patterns
+        .iter()
+        .map(|pattern| {
+            glyphs
+                .iter()
+                .enumerate()
+                .filter(|(_, glyph)| glyph.segments.count_ones() == pattern.count_ones())
+                .fold(0, |mask, (i, _)| mask | 1 << i)
+        })
+        .collect();
+    let mut search = Search {
+        glyphs,
+        patterns,
+        all_segments,
+        assigned: vec![None; wire_count],
+        solutions: Vec::new(),
+    };
+    search.run(&candidates, 0);
+    match search.solutions.len() {
+        0 => Err(SolveError::NoConsistentMapping),
+        1 => Ok(search.solutions.remove(0)),
+        _ => Err(SolveError::AmbiguousMapping),
+    }
+}
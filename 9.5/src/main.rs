@@ -1,4 +1,6 @@
-use std::collections::HashSet;
+mod render;
+
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
@@ -31,10 +33,59 @@ impl From<std::num::ParseIntError> for MainError {
 struct Board {
     // Rows, then columns.
     data: Vec<Vec<u32>>,
+    rules: BasinRules,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Flow {
+    // Climb out from each low point through strictly higher cells.
+    Rising,
+    // Like Rising, but also through cells of equal height, so plateaus aren't split up.
+    Plateau,
+    // Each cell drains to its lowest lower neighbour, and a basin is everything that drains to
+    // the same sink. A flat stretch with no way down is one sink. A flat shelf with a way down
+    // drains as one, through the cell on it whose way down is lowest (the first in reading
+    // order on a tie), so a shelf between two basins is never split or used to join them.
+    Downhill,
+}
+
+#[derive(Clone, Copy)]
+struct BasinRules {
+    // Whether diagonal cells are neighbours too.
+    diagonal: bool,
+    // Cells this high or higher are never in a basin.
+    wall: u32,
+    flow: Flow,
+}
+
+impl Default for BasinRules {
+    // The puzzle's rules.
+    fn default() -> Self {
+        BasinRules {
+            diagonal: false,
+            wall: 9,
+            flow: Flow::Rising,
+        }
+    }
 }
 
 type Coord = (usize, usize);
 
+struct Basin {
+    low_point: Coord,
+    size: usize,
+    lowest: u32,
+    highest: u32,
+}
+
+struct BasinMap {
+    // Index into `basins` for each cell, rows then columns. None for cells in no basin. A cell
+    // reachable from several low points keeps the first basin's label, but counts towards the
+    // size of each.
+    labels: Vec<Vec<Option<u32>>>,
+    basins: Vec<Basin>,
+}
+
 impl Board {
     /*
     fn new_row(&mut self, row: Vec<u32>) {
@@ -43,7 +94,7 @@ impl Board {
     */
 
     fn width(&self) -> usize {
-        self.data.get(0).map(|row| row.len()).unwrap_or(0)
+        self.data.first().map(|row| row.len()).unwrap_or(0)
     }
 
     fn height(&self) -> usize {
@@ -55,60 +106,279 @@ impl Board {
         self.data[y][x]
     }
 
-    // Returns all coords connected to this coord in a basin. Ignore all elements in the ignore list.
-    fn basin_elements(&self, position: Coord, ignore: &mut HashSet<Coord>) -> HashSet<Coord> {
-        let mut ret = HashSet::new();
-        ret.insert(position);
-        ignore.insert(position);
-        let (x, y) = position;
-        let mut coords_to_check = Vec::new();
-        if x > 0 && !ignore.contains(&(x - 1, y)) {
-            coords_to_check.push((x - 1, y));
+    // The up to four coords next to this one, or eight with diagonals.
+    fn neighbours(&self, coord: Coord) -> Vec<Coord> {
+        let (x, y) = coord;
+        let mut ret = Vec::new();
+        for (dx, dy) in [
+            (-1, 0),
+            (0, -1),
+            (1, 0),
+            (0, 1),
+            (-1, -1),
+            (1, -1),
+            (-1, 1),
+            (1, 1),
+        ]
+        .iter()
+        .take(if self.rules.diagonal { 8 } else { 4 })
+        {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if 0 <= nx && (nx as usize) < self.width() && 0 <= ny && (ny as usize) < self.height() {
+                ret.push((nx as usize, ny as usize));
+            }
         }
-        if y > 0 && !ignore.contains(&(x, y - 1)) {
-            coords_to_check.push((x, y - 1));
+        ret
+    }
+
+    fn is_wall(&self, coord: Coord) -> bool {
+        self.get(coord) >= self.rules.wall
+    }
+
+    // Returns all coords connected to this coord in a basin, searching breadth first so large
+    // basins can't overflow the stack. `seen` is shared between searches: a cell holding `mark`
+    // has already been reached by this one, so it never needs clearing.
+    fn basin_elements(&self, position: Coord, seen: &mut [Vec<u32>], mark: u32) -> Vec<Coord> {
+        let mut ret = Vec::new();
+        let mut queue = VecDeque::from([position]);
+        seen[position.1][position.0] = mark;
+        while let Some(coord) = queue.pop_front() {
+            ret.push(coord);
+            for next in self.neighbours(coord) {
+                let climbs = match self.rules.flow {
+                    Flow::Plateau => self.get(coord) <= self.get(next),
+                    _ => self.get(coord) < self.get(next),
+                };
+                if seen[next.1][next.0] != mark && climbs && !self.is_wall(next) {
+                    seen[next.1][next.0] = mark;
+                    queue.push_back(next);
+                }
+            }
         }
-        if x < self.width() - 1 && !ignore.contains(&(x + 1, y)) {
-            coords_to_check.push((x + 1, y));
+        ret
+    }
+
+    // The stretch of cells of equal height connected to `coord`, marking them in `seen`.
+    fn flat(&self, coord: Coord, seen: &mut [Vec<bool>]) -> Vec<Coord> {
+        let height = self.get(coord);
+        let mut ret = Vec::new();
+        let mut queue = VecDeque::from([coord]);
+        seen[coord.1][coord.0] = true;
+        while let Some(coord) = queue.pop_front() {
+            ret.push(coord);
+            for next in self.neighbours(coord) {
+                if self.get(next) == height && !seen[next.1][next.0] {
+                    seen[next.1][next.0] = true;
+                    queue.push_back(next);
+                }
+            }
         }
-        if y < self.height() - 1 && !ignore.contains(&(x, y + 1)) {
-            coords_to_check.push((x, y + 1));
+        ret
+    }
+
+    fn has_lower_neighbour(&self, coord: Coord) -> bool {
+        self.neighbours(coord)
+            .iter()
+            .any(|&next| self.get(next) < self.get(coord))
+    }
+
+    // Finds every basin, in reading order of their low points.
+    fn label_basins(&self) -> BasinMap {
+        let mut map = BasinMap {
+            labels: vec![vec![None; self.width()]; self.height()],
+            basins: Vec::new(),
+        };
+        if self.rules.flow == Flow::Downhill {
+            self.label_drainage(&mut map);
+        } else {
+            self.label_climbs(&mut map);
+        }
+        map
+    }
+
+    fn add_basin(&self, map: &mut BasinMap, low_point: Coord, elements: &[Coord]) {
+        for &(x, y) in elements {
+            map.labels[y][x].get_or_insert(map.basins.len() as u32);
         }
-        for coord in coords_to_check {
-            if self.get(position) < self.get(coord) && self.get(coord) < 9 {
-                ret.extend(self.basin_elements(coord, ignore));
+        let heights = elements.iter().map(|&coord| self.get(coord));
+        map.basins.push(Basin {
+            low_point,
+            size: elements.len(),
+            lowest: heights.clone().min().unwrap_or(0),
+            highest: heights.max().unwrap_or(0),
+        });
+    }
+
+    // Climbs out from every low point.
+    fn label_climbs(&self, map: &mut BasinMap) {
+        let mut seen = vec![vec![0; self.width()]; self.height()];
+        let mut flat_seen = match self.rules.flow {
+            Flow::Plateau => vec![vec![false; self.width()]; self.height()],
+            _ => Vec::new(),
+        };
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if self.is_wall((x, y)) {
+                    continue;
+                }
+                let val = self.get((x, y));
+                let low_point = match self.rules.flow {
+                    // A flat stretch is a valley floor if no cell on it borders a lower one. Its
+                    // first cell in reading order will do, as the climb covers the rest.
+                    Flow::Plateau => {
+                        !flat_seen[y][x]
+                            && self
+                                .flat((x, y), &mut flat_seen)
+                                .into_iter()
+                                .all(|coord| !self.has_lower_neighbour(coord))
+                    }
+                    _ => self
+                        .neighbours((x, y))
+                        .iter()
+                        .all(|&coord| val < self.get(coord)),
+                };
+                if low_point {
+                    // Found basin bottom!
+                    let mark = map.basins.len() as u32 + 1;
+                    let elements = self.basin_elements((x, y), &mut seen, mark);
+                    self.add_basin(map, (x, y), &elements);
+                }
+            }
+        }
+    }
+
+    // Groups cells by the sink they drain to, using union-find over cell indices.
+    fn label_drainage(&self, map: &mut BasinMap) {
+        let width = self.width();
+        let mut parent: Vec<usize> = (0..width * self.height()).collect();
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        let drains_to = |coord: Coord| {
+            self.neighbours(coord)
+                .into_iter()
+                .filter(|&next| self.get(next) < self.get(coord))
+                .min_by_key(|&next| self.get(next))
+        };
+        let mut union = |a: Coord, b: Coord| {
+            let a = root(&mut parent, a.1 * width + a.0);
+            let b = root(&mut parent, b.1 * width + b.0);
+            parent[a] = b;
+        };
+        let mut flat_seen = vec![vec![false; width]; self.height()];
+        for y in 0..self.height() {
+            for x in 0..width {
+                if self.is_wall((x, y)) {
+                    continue;
+                }
+                if let Some(next) = drains_to((x, y)) {
+                    union((x, y), next);
+                    continue;
+                }
+                if flat_seen[y][x] {
+                    continue;
+                }
+                // No way down from here, so drain with the rest of the flat stretch: through
+                // its lowest way down if it has one, otherwise it's a sink of its own.
+                let flat = self.flat((x, y), &mut flat_seen);
+                let outlet = flat
+                    .iter()
+                    .filter_map(|&coord| Some((self.get(drains_to(coord)?), coord)))
+                    .min_by_key(|&(height, (x, y))| (height, y, x))
+                    .map_or((x, y), |(_, coord)| coord);
+                for coord in flat {
+                    if drains_to(coord).is_none() {
+                        union(coord, outlet);
+                    }
+                }
+            }
+        }
+        // Basins are numbered in reading order of their first cells, and their sizes and
+        // heights tallied as cells come in, so no basin needs its cells listed.
+        let mut labels: HashMap<usize, u32> = HashMap::new();
+        for y in 0..self.height() {
+            for x in 0..width {
+                if self.is_wall((x, y)) {
+                    continue;
+                }
+                let r = root(&mut parent, y * width + x);
+                let height = self.get((x, y));
+                let label = *labels.entry(r).or_insert_with(|| {
+                    map.basins.push(Basin {
+                        low_point: (x, y),
+                        size: 0,
+                        lowest: height,
+                        highest: height,
+                    });
+                    map.basins.len() as u32 - 1
+                });
+                map.labels[y][x] = Some(label);
+                let basin = &mut map.basins[label as usize];
+                basin.size += 1;
+                if height < basin.lowest {
+                    basin.lowest = height;
+                    basin.low_point = (x, y);
+                }
+                basin.highest = basin.highest.max(height);
             }
         }
-        ret
     }
 }
 
 fn main() -> Result<(), MainError> {
+    // --diagonal, --wall <height> and --flow rising|plateau|downhill change what counts as a
+    // basin; see BasinRules.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let value = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .map(|i| args.get(i + 1).ok_or("Missing value"))
+            .transpose()
+    };
+    let mut rules = BasinRules {
+        diagonal: args.iter().any(|arg| arg == "--diagonal"),
+        ..BasinRules::default()
+    };
+    if let Some(wall) = value("--wall")? {
+        rules.wall = wall.parse()?;
+    }
+    rules.flow = match value("--flow")?.map(|flow| flow.as_str()) {
+        None | Some("rising") => Flow::Rising,
+        Some("plateau") => Flow::Plateau,
+        Some("downhill") => Flow::Downhill,
+        Some(_) => return Err("--flow takes rising, plateau or downhill".into()),
+    };
     let board = Board {
         data: read_lines("./input")?
-            .flatten()
+            .map_while(Result::ok)
             .map(|line| line.chars().filter_map(|c| c.to_digit(10)).collect())
             .collect(),
+        rules,
     };
-    let mut basins = Vec::new();
-    for y in 0..board.height() {
-        for x in 0..board.width() {
-            let val = board.get((x, y));
-            if (y == 0 || val < board.get((x, y - 1)))
-                && (y == board.height() - 1 || val < board.get((x, y + 1)))
-                && (x == 0 || val < board.get((x - 1, y)))
-                && (x == board.width() - 1 || val < board.get((x + 1, y)))
-            {
-                // Found basin bottom!
-                basins.push(board.basin_elements((x, y), &mut HashSet::new()));
-            }
-        }
+    let map = board.label_basins();
+    // --render ansi draws the basins in the terminal; --render <file.ppm> saves them as an image.
+    match value("--render")?.map(|target| target.as_str()) {
+        Some("ansi") => print!("{}", render::ansi(&board, &map)),
+        Some(path) => render::write_ppm(&map, path)?,
+        None => (),
     }
-    basins.sort_by(|basin1, basin2| basin2.len().cmp(&basin1.len()));
-    let mut result = 1;
-    for i in 0..3 {
-        result *= basins[i].len();
+    // --basins lists every basin.
+    if args.iter().any(|arg| arg == "--basins") {
+        for basin in &map.basins {
+            println!(
+                "low point {:?}: size {}, heights {}-{}",
+                basin.low_point, basin.size, basin.lowest, basin.highest
+            );
+        }
     }
+    let mut sizes: Vec<usize> = map.basins.iter().map(|basin| basin.size).collect();
+    sizes.sort_by_key(|&size| std::cmp::Reverse(size));
+    let result: usize = sizes.iter().take(3).product();
     println!("{}", result);
     Ok(())
 }
@@ -0,0 +1,67 @@
+// Draws labelled basins, each in its own colour, for seeing where basins meet.
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::{BasinMap, Board};
+
+// Cells outside every basin.
+const WALL: [u8; 3] = [40, 40, 40];
+
+// Spreads labels around the colour wheel so neighbouring basins rarely look alike.
+fn colour(label: usize) -> [u8; 3] {
+    let hue = (label as f64 * 0.618_033_988_75).fract() * 6.0;
+    let rising = hue.fract();
+    let (r, g, b) = match hue as u32 {
+        0 => (1.0, rising, 0.0),
+        1 => (1.0 - rising, 1.0, 0.0),
+        2 => (0.0, 1.0, rising),
+        3 => (0.0, 1.0 - rising, 1.0),
+        4 => (rising, 0.0, 1.0),
+        _ => (1.0, 0.0, 1.0 - rising),
+    };
+    // Keep it light enough for dark text on top.
+    let shade = |c: f64| (110.0 + c * 145.0) as u8;
+    [shade(r), shade(g), shade(b)]
+}
+
+fn cell_colour(map: &BasinMap, x: usize, y: usize) -> [u8; 3] {
+    map.labels[y][x].map_or(WALL, |label| colour(label as usize))
+}
+
+// Heights on basin colours, with each low point in bold.
+pub fn ansi(board: &Board, map: &BasinMap) -> String {
+    let mut out = String::new();
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            let [r, g, b] = cell_colour(map, x, y);
+            let low_point = map.labels[y][x]
+                .is_some_and(|label| map.basins[label as usize].low_point == (x, y));
+            out.push_str(&format!(
+                "\x1b[{}48;2;{};{};{}m\x1b[38;2;0;0;0m{}",
+                if low_point { "1;" } else { "" },
+                r,
+                g,
+                b,
+                board.get((x, y))
+            ));
+            out.push_str("\x1b[0m");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// One pixel per cell, as a binary PPM.
+pub fn write_ppm<P: AsRef<Path>>(map: &BasinMap, path: P) -> io::Result<()> {
+    let height = map.labels.len();
+    let width = map.labels.first().map_or(0, |row| row.len());
+    let mut file = BufWriter::new(File::create(path)?);
+    write!(file, "P6\n{} {}\n255\n", width, height)?;
+    for y in 0..height {
+        for x in 0..width {
+            file.write_all(&cell_colour(map, x, y))?;
+        }
+    }
+    file.flush()
+}
@@ -0,0 +1,70 @@
+// The cave system both parts of Day 12 search for routes through.
+use std::collections::HashMap;
+
+pub type NodeId = usize;
+
+// Caves and their tunnels, with each cave's name interned to an index into the lists below.
+pub struct CaveGraph {
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    // Small caves are the ones with no uppercase letters in their names; they're worked out
+    // once, up front.
+    small: Vec<bool>,
+    adjacent: Vec<Vec<NodeId>>,
+}
+
+impl CaveGraph {
+    fn new() -> CaveGraph {
+        CaveGraph {
+            names: Vec::new(),
+            ids: HashMap::new(),
+            small: Vec::new(),
+            adjacent: Vec::new(),
+        }
+    }
+
+    // Parses tunnels, one "a-b" per line.
+    pub fn parse<I: IntoIterator<Item = String>>(lines: I) -> Result<CaveGraph, &'static str> {
+        let mut graph = CaveGraph::new();
+        for line in lines {
+            let (a, b) = line.split_once('-').ok_or("Malformed connection")?;
+            graph.add_connection(a, b);
+        }
+        Ok(graph)
+    }
+
+    fn intern(&mut self, name: &str) -> NodeId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_owned());
+        self.ids.insert(name.to_owned(), id);
+        self.small.push(!name.chars().any(char::is_uppercase));
+        self.adjacent.push(Vec::new());
+        id
+    }
+
+    pub fn add_connection(&mut self, a: &str, b: &str) {
+        let a = self.intern(a);
+        let b = self.intern(b);
+        self.adjacent[a].push(b);
+        self.adjacent[b].push(a);
+    }
+
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn cave_count(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_small(&self, node: NodeId) -> bool {
+        self.small[node]
+    }
+
+    pub fn neighbours(&self, node: NodeId) -> &[NodeId] {
+        &self.adjacent[node]
+    }
+}
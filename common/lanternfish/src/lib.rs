@@ -0,0 +1,78 @@
+// The lanternfish population model both parts of Day 6 run.
+use std::collections::VecDeque;
+
+/**
+ * Counts fish by timer rather than tracking each one. Fish that have spawned before cycle
+ * through `veterans`; newborns wait in `newbies` until their timer first drops to the reset
+ * timer.
+ */
+pub struct LanternfishModel {
+    // Index is the timer.
+    veterans: VecDeque<u64>,
+    // Index is the timer minus (reset timer + 1).
+    newbies: VecDeque<u64>,
+}
+
+impl LanternfishModel {
+    pub fn new(reset_timer: usize, newborn_timer: usize) -> Result<LanternfishModel, &'static str> {
+        if newborn_timer < reset_timer {
+            return Err("Newborn timer must be at least the reset timer");
+        }
+        Ok(LanternfishModel {
+            veterans: VecDeque::from(vec![0; reset_timer + 1]),
+            newbies: VecDeque::from(vec![0; newborn_timer - reset_timer]),
+        })
+    }
+
+    pub fn add_fish(&mut self, timer: usize) -> Result<(), &'static str> {
+        if timer < self.veterans.len() {
+            self.veterans[timer] += 1;
+        } else if let Some(count) = self.newbies.get_mut(timer - self.veterans.len()) {
+            *count += 1;
+        } else {
+            return Err("Fish timer is above the newborn timer");
+        }
+        Ok(())
+    }
+
+    pub fn step(&mut self) {
+        let new_fishies = self.veterans.pop_front().unwrap_or(0);
+        self.newbies.push_back(new_fishies);
+        let vet_fishies = self.newbies.pop_front().unwrap_or(0);
+        self.veterans.push_back(new_fishies + vet_fishies); // new_fishies in this case means fish that just gave birth
+    }
+
+    pub fn reset_timer(&self) -> usize {
+        self.veterans.len() - 1
+    }
+
+    // Fish counts indexed by timer, from 0 up to the newborn timer.
+    pub fn timers(&self) -> Vec<u64> {
+        self.veterans.iter().chain(&self.newbies).copied().collect()
+    }
+
+    /**
+     * The factor the population eventually grows by each day, i.e. the dominant eigenvalue of
+     * the transition. A fish at timer 0 is back at timer 0 after reset + 1 days and its child
+     * after newborn + 1 days, so this is the root of x^-(reset + 1) + x^-(newborn + 1) = 1,
+     * which always lies in (1, 2].
+     */
+    pub fn growth_rate(&self) -> f64 {
+        let parent_cycle = self.veterans.len() as i32;
+        let child_cycle = (self.veterans.len() + self.newbies.len()) as i32;
+        let (mut low, mut high) = (1.0_f64, 2.0_f64);
+        for _ in 0..64 {
+            let mid = (low + high) / 2.0;
+            if mid.powi(-parent_cycle) + mid.powi(-child_cycle) > 1.0 {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    pub fn population(&self) -> u64 {
+        self.veterans.iter().sum::<u64>() + self.newbies.iter().sum::<u64>()
+    }
+}
@@ -0,0 +1,192 @@
+// The octopus grid both parts of Day 11 simulate, with configurable flash rules.
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(Clone, Copy)]
+pub struct Rules {
+    // An octopus at this energy flashes the next time it's energized.
+    pub threshold: u32,
+    // Whether diagonal octopi are neighbours too.
+    pub diagonal: bool,
+    // Whether the edges wrap around, making the grid a torus.
+    pub wrap: bool,
+}
+
+impl Default for Rules {
+    // The puzzle's rules.
+    fn default() -> Self {
+        Rules {
+            threshold: 9,
+            diagonal: true,
+            wrap: false,
+        }
+    }
+}
+
+impl Rules {
+    // Reads --threshold <energy>, --neighbours 4|8 and --wrap.
+    pub fn from_args(args: &[String]) -> Result<Rules, &'static str> {
+        let value = |name: &str| {
+            args.iter()
+                .position(|arg| arg == name)
+                .map(|i| args.get(i + 1).ok_or("Missing value"))
+                .transpose()
+        };
+        let mut rules = Rules {
+            wrap: args.iter().any(|arg| arg == "--wrap"),
+            ..Rules::default()
+        };
+        if let Some(threshold) = value("--threshold")? {
+            rules.threshold = threshold.parse().map_err(|_| "Invalid threshold")?;
+        }
+        rules.diagonal = match value("--neighbours")?.map(|n| n.as_str()) {
+            None | Some("8") => true,
+            Some("4") => false,
+            Some(_) => return Err("--neighbours takes 4 or 8"),
+        };
+        Ok(rules)
+    }
+}
+
+#[derive(Clone)]
+pub struct Board {
+    // Rows, then columns.
+    data: Vec<Vec<u32>>,
+    flashed: Vec<Vec<bool>>,
+    rules: Rules,
+}
+
+pub type Coord = (usize, usize);
+
+impl Board {
+    pub fn new(data: Vec<Vec<u32>>, rules: Rules) -> Board {
+        Board {
+            flashed: vec![vec![false; data.first().map_or(0, |row| row.len())]; data.len()],
+            data,
+            rules,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.data.first().map(|row| row.len()).unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.data.len()
+    }
+
+    // Only the energy levels carry over between steps, so they're the whole state.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.data.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    // Energy levels, rows then columns.
+    pub fn energies(&self) -> &[Vec<u32>] {
+        &self.data
+    }
+
+    pub fn get(&self, coord: Coord) -> u32 {
+        let (x, y) = coord;
+        self.data[y][x]
+    }
+
+    pub fn get_flashed(&self, coord: Coord) -> bool {
+        let (x, y) = coord;
+        self.flashed[y][x]
+    }
+
+    fn get_mut(&mut self, coord: Coord) -> &mut u32 {
+        let (x, y) = coord;
+        self.data.get_mut(y).unwrap().get_mut(x).unwrap()
+    }
+
+    fn set_flashed(&mut self, coord: Coord) {
+        let (x, y) = coord;
+        self.flashed[y][x] = true;
+    }
+
+    fn get_adjacent(&self, coord: Coord) -> Vec<Coord> {
+        let (x, y) = (coord.0 as isize, coord.1 as isize);
+        let (width, height) = (self.width() as isize, self.height() as isize);
+        let offsets: &[(isize, isize)] = if self.rules.diagonal {
+            &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ]
+        } else {
+            &[(0, -1), (-1, 0), (1, 0), (0, 1)]
+        };
+        let mut ret = Vec::new();
+        for (dx, dy) in offsets {
+            let (mut ax, mut ay) = (x + dx, y + dy);
+            if self.rules.wrap {
+                ax = ax.rem_euclid(width);
+                ay = ay.rem_euclid(height);
+            } else if ax < 0 || ax >= width || ay < 0 || ay >= height {
+                continue;
+            }
+            let adjacent = (ax as usize, ay as usize);
+            // On narrow grids, wrapping can reach the same octopus twice or loop back to this one.
+            if adjacent != coord && !ret.contains(&adjacent) {
+                ret.push(adjacent);
+            }
+        }
+        ret
+    }
+
+    // Raises one octopus's energy, returning true if that makes it flash.
+    fn energize(&mut self, coord: Coord) -> bool {
+        if self.get_flashed(coord) {
+            return false;
+        }
+        let threshold = self.rules.threshold;
+        let val = self.get_mut(coord);
+        if *val < threshold {
+            *val += 1;
+            false
+        } else {
+            *val = 0; // Flash!
+            self.set_flashed(coord);
+            true
+        }
+    }
+
+    /**
+     * Steps the board by one cycle, returning the number of octopi who flashed. Flashes waiting
+     * to energize their neighbours go on a worklist rather than the call stack.
+     */
+    pub fn step(&mut self) -> u32 {
+        // Reset
+        self.flashed = vec![vec![false; self.width()]; self.height()];
+        let mut pending = Vec::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if self.energize((x, y)) {
+                    pending.push((x, y));
+                }
+            }
+        }
+        let mut flash_count = 0;
+        while let Some(coord) = pending.pop() {
+            flash_count += 1;
+            for adjacent in self.get_adjacent(coord) {
+                if self.energize(adjacent) {
+                    pending.push(adjacent);
+                }
+            }
+        }
+        flash_count
+    }
+}